@@ -1,4 +1,9 @@
 fn main() {
+    // Host builds, for testing the effects, link normally.
+    if std::env::var_os("CARGO_FEATURE_ESP").is_none() {
+        return;
+    }
+
     linker_be_nice();
     // make sure linkall.x is the last linker script (otherwise might cause problems with flip-link)
     println!("cargo:rustc-link-arg=-Tlinkall.x");
@@ -6,18 +6,25 @@ extern crate alloc;
 use alloc::boxed::Box;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::mem;
 use embassy_executor::Spawner;
 use embassy_time::{Duration, Timer};
 use esp_backtrace as _;
 use esp_hal::clock::CpuClock;
 use log::info;
 
-use firmware::effect::{Effect, FadeCurve, FadeDirection, FadeTransitionEffect, SinePulseEffect};
+use firmware::effect::{
+    Effect, EffectId, EffectStack, FadeCurve, FadeDirection, FadeTransitionEffect, ModulatorEffect,
+    PlasmaEffect, PlaylistEffect, PlaylistTransition, RainbowCycleEffect,
+};
 use firmware::event::{button_input, charger_input, Event};
 use firmware::power::PowerState;
-use firmware::render::{renderer, Rgb};
+use firmware::render::{renderer, Palette, Rgb};
 use firmware::state::{Mode, MutexGuard, State};
 
+/// The playlist of scenes shown while the prop is on.
+const MAIN_PLAYLIST: EffectId = EffectId(1);
+
 #[esp_hal_embassy::main]
 async fn main(spawner: Spawner) {
     esp_alloc::heap_allocator!(size: 256 * 1024);
@@ -33,6 +40,8 @@ async fn main(spawner: Spawner) {
     spawner.spawn(renderer()).unwrap();
 
     let mut initial_hold = false;
+    // Set while the button is down, until it's been held long enough to count as a hold.
+    let mut short_press = false;
 
     'main: loop {
         match state.get_mode().await {
@@ -75,7 +84,7 @@ async fn main(spawner: Spawner) {
             Mode::PreCharging => {
                 let charging_effect: Vec<Box<dyn Effect>> = vec![
                     Box::new(Rgb::WHITE),
-                    Box::new(SinePulseEffect::new(
+                    Box::new(ModulatorEffect::sine_pulse(
                         None,
                         Duration::from_millis(5000),
                         0.075,
@@ -116,11 +125,11 @@ async fn main(spawner: Spawner) {
                 }
             }
             Mode::PreMain => {
-                let main_effect: Vec<Box<dyn Effect>> = vec![
+                let pulse_scene: Vec<Box<dyn Effect>> = vec![
                     // Solid cyan.
                     Box::new(Rgb::new(0.0, 1.0, 1.0)),
                     // Pulse with red.
-                    Box::new(SinePulseEffect::new(
+                    Box::new(ModulatorEffect::sine_pulse(
                         None,
                         Duration::from_millis(3000),
                         0.5,
@@ -128,6 +137,39 @@ async fn main(spawner: Spawner) {
                         Some(Box::new(Rgb::new(1.0, 0.0, 0.0))),
                     )),
                 ];
+                let rainbow_scene = RainbowCycleEffect::new(
+                    None,
+                    Duration::from_millis(10000),
+                    1.0,
+                    1.0,
+                    1.0,
+                    None,
+                );
+                let plasma_scene = PlasmaEffect::new(
+                    None,
+                    0,
+                    Palette::even(&[
+                        Rgb::new(0.0, 0.0, 1.0),
+                        Rgb::new(0.0, 1.0, 1.0),
+                        Rgb::new(1.0, 0.0, 1.0),
+                    ]),
+                    0.05,
+                    0.2,
+                );
+
+                let main_effect = PlaylistEffect::new(
+                    Some(MAIN_PLAYLIST),
+                    vec![
+                        (Box::new(pulse_scene), Duration::from_secs(60)),
+                        (Box::new(rainbow_scene), Duration::from_secs(60)),
+                        (Box::new(plasma_scene), Duration::from_secs(60)),
+                    ],
+                    PlaylistTransition::Fade {
+                        duration: Duration::from_millis(2000),
+                        curve: FadeCurve::Smoothstep,
+                    },
+                    None,
+                );
 
                 let mut effect_stack = state.effect_stack.lock().await;
                 add_fade_in(&mut effect_stack, Some(Box::new(main_effect)), 1000);
@@ -139,9 +181,11 @@ async fn main(spawner: Spawner) {
                 Event::ButtonPress => {
                     info!("Button press!");
                     initial_hold = false;
+                    short_press = true;
                 }
                 Event::ButtonHold => {
                     info!("Button hold!");
+                    short_press = false;
                     if initial_hold {
                         state.set_mode(Mode::Pairing).await;
                         continue 'main;
@@ -154,6 +198,19 @@ async fn main(spawner: Spawner) {
                 Event::ButtonRelease => {
                     info!("Button release!");
                     initial_hold = false;
+
+                    // Holds turn the prop off, so only short presses skip ahead.
+                    if mem::take(&mut short_press) {
+                        if let Some(playlist) = state
+                            .effect_stack
+                            .lock()
+                            .await
+                            .find_effect_mut(MAIN_PLAYLIST)
+                            .and_then(|effect| effect.downcast_mut::<PlaylistEffect>())
+                        {
+                            playlist.next();
+                        }
+                    }
                 }
                 Event::ChargerPluggedIn => {
                     let mut effect_stack = state.effect_stack.lock().await;
@@ -0,0 +1,63 @@
+use embassy_time::{Duration, Instant};
+
+/// The time of the frame being rendered, which effects derive all of their timing from.
+///
+/// Effects never read the system time themselves, so that a clock can be paused, scaled, or
+/// replayed, and so that the same clock always renders the same frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameClock {
+    /// The timestamp of the current frame.
+    pub now: Instant,
+    /// The time since the previous frame.
+    pub elapsed: Duration,
+}
+
+impl FrameClock {
+    /// Creates a clock at `now` that hasn't ticked yet.
+    pub const fn new(now: Instant) -> Self {
+        Self {
+            now,
+            elapsed: Duration::from_ticks(0),
+        }
+    }
+
+    /// Advances the clock to a new frame at `now`.
+    pub fn tick(&mut self, now: Instant) {
+        self.elapsed = now.saturating_duration_since(self.now);
+        self.now = now;
+    }
+
+    /// The time since `start`, or zero if `start` hasn't happened yet.
+    pub fn since(&self, start: Instant) -> Duration {
+        self.now.saturating_duration_since(start)
+    }
+
+    /// How far through `duration` the clock is, from `0.0` at `start` to `1.0` at the end.
+    pub fn progress(&self, start: Instant, duration: Duration) -> f32 {
+        if duration.as_ticks() == 0 {
+            return 1.0;
+        }
+
+        (self.since(start).as_ticks() as f32 / duration.as_ticks() as f32).min(1.0)
+    }
+
+    /// How many whole `period`s have passed since `start`.
+    pub fn cycles(&self, start: Instant, period: Duration) -> u64 {
+        match period.as_ticks() {
+            0 => 0,
+            period => self.since(start).as_ticks() / period,
+        }
+    }
+
+    /// How far through a repeating `period` that began at `start` the clock is, from `0.0` up
+    /// to, but not including, `1.0`. Times before `start` count backwards through earlier periods.
+    pub fn phase(&self, start: Instant, period: Duration) -> f32 {
+        let period = period.as_ticks() as i64;
+        if period == 0 {
+            return 0.0;
+        }
+
+        let offset = self.now.as_ticks() as i64 - start.as_ticks() as i64;
+        offset.rem_euclid(period) as f32 / period as f32
+    }
+}
@@ -0,0 +1,175 @@
+use embassy_time::{Duration, Instant};
+use micromath::F32Ext;
+
+use crate::effect::{BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock};
+use crate::render::{Rgb, Rgba, LED_COUNT};
+
+pub enum CometDirection {
+    /// The comet travels from the first pixel towards the last.
+    Forward,
+    /// The comet travels from the last pixel towards the first.
+    Backward,
+}
+
+pub enum CometBoundary {
+    /// The comet leaves one end of the strip and re-enters at the other.
+    Wrap,
+    /// The comet reverses direction at each end of the strip.
+    Bounce,
+}
+
+impl CometBoundary {
+    /// The distance, in pixels, that the comet travels before it's back where it started.
+    fn cycle_length(&self) -> f32 {
+        match self {
+            Self::Wrap => LED_COUNT as f32,
+            Self::Bounce => 2.0 * (LED_COUNT - 1) as f32,
+        }
+    }
+}
+
+pub struct CometEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// How far the head has travelled through the current cycle, in pixels.
+    distance: f32,
+    color: Rgb,
+    /// Pixels per second.
+    speed: f32,
+    /// The length of the tail, in pixels.
+    length: f32,
+    /// The exponent of the tail's falloff. `1.0` fades linearly, higher values fade faster.
+    tail_decay: f32,
+    direction: CometDirection,
+    boundary: CometBoundary,
+}
+
+impl CometEffect {
+    pub fn new(
+        id: Option<EffectId>,
+        color: Rgb,
+        speed: f32,
+        length: f32,
+        tail_decay: f32,
+        direction: CometDirection,
+        boundary: CometBoundary,
+    ) -> Self {
+        Self {
+            id,
+            start: None,
+            distance: 0.0,
+            color,
+            speed,
+            length,
+            tail_decay,
+            direction,
+            boundary,
+        }
+    }
+
+    fn period(&self) -> Duration {
+        Duration::from_micros((self.boundary.cycle_length() / self.speed * 1_000_000.0) as u64)
+    }
+
+    /// The brightness of a pixel that is `behind` pixels behind the head.
+    fn intensity(&self, behind: f32) -> f32 {
+        if behind < 0.0 {
+            // Anti-alias the leading edge of the head.
+            (1.0 + behind).max(0.0)
+        } else if behind < self.length {
+            (1.0 - behind / self.length).powf(self.tail_decay)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Effect for CometEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let start = *self.start.get_or_insert(clock.now);
+        self.distance = clock.phase(start, self.period()) * self.boundary.cycle_length();
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], _pool: &mut BufferPool) {
+        let cycle = self.boundary.cycle_length();
+
+        // How far the head has travelled since it passed `distance` through the cycle. Points
+        // just ahead of the head come out slightly negative, for anti-aliasing its leading edge.
+        let since = |distance: f32| {
+            let behind = (self.distance - distance).rem_euclid(cycle);
+            if behind > cycle - 1.0 {
+                behind - cycle
+            } else {
+                behind
+            }
+        };
+
+        for (pixel, i) in buffer.iter_mut().zip(offset..) {
+            let position = match self.direction {
+                CometDirection::Forward => i as f32,
+                CometDirection::Backward => (LED_COUNT - 1 - i) as f32,
+            };
+
+            let intensity = match self.boundary {
+                CometBoundary::Wrap => self.intensity(since(position)),
+                // The head passes each pixel on the way out and again on the way back, so the
+                // tail follows it around each end of the strip.
+                CometBoundary::Bounce => self
+                    .intensity(since(position))
+                    .max(self.intensity(since(cycle - position))),
+            };
+
+            *pixel = Rgba::from_rgb(self.color, intensity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::effect::CHUNK_SIZE;
+
+    #[test]
+    fn bounce_keeps_the_tail_through_the_turnaround() {
+        let mut comet = CometEffect::new(
+            None,
+            Rgb::WHITE,
+            100.0,
+            20.0,
+            1.0,
+            CometDirection::Forward,
+            CometBoundary::Bounce,
+        );
+        let mut pool = BufferPool::new();
+        let mut buffer = vec![Rgba::TRANSPARENT; LED_COUNT];
+
+        // The head reaches the last pixel at 1990ms.
+        let mut clock = FrameClock::new(Instant::from_millis(0));
+        comet.update(&clock);
+        for ms in (1800..2200).step_by(5) {
+            clock.tick(Instant::from_millis(ms));
+            comet.update(&clock);
+
+            for (i, chunk) in buffer.chunks_mut(CHUNK_SIZE).enumerate() {
+                comet.apply(i * CHUNK_SIZE, chunk, &mut pool);
+            }
+
+            // Folded back over the end of the strip, the tail covers at least half its length.
+            let lit = buffer.iter().filter(|pixel| pixel.a > 0.0).count();
+            assert!(lit >= 10, "{ms}ms: {lit} pixels lit");
+            assert!(buffer[..LED_COUNT - 45].iter().all(|pixel| pixel.a <= 0.0));
+        }
+    }
+}
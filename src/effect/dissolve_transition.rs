@@ -0,0 +1,95 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use embassy_time::{Duration, Instant};
+
+use crate::effect::{
+    BlendMode, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FadeCurve, FadeDirection,
+    FrameClock, Rng, Wrapped,
+};
+use crate::render::{Rgba, LED_COUNT};
+
+/// Reveals or hides the wrapped effect one pixel at a time, in a random order.
+pub struct DissolveTransitionEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// How far through the transition the effect is, from `0.0` to `1.0`.
+    progress: f32,
+    duration: Duration,
+    fade_curve: FadeCurve,
+    fade_direction: FadeDirection,
+    /// The position of each pixel in the reveal order.
+    order: Vec<u16>,
+    wrapped: Wrapped,
+}
+
+impl DissolveTransitionEffect {
+    pub fn new(
+        id: Option<EffectId>,
+        duration: Duration,
+        fade_curve: FadeCurve,
+        fade_direction: FadeDirection,
+        seed: u32,
+        wrapped: Option<Box<dyn Effect>>,
+    ) -> Self {
+        let mut order: Vec<u16> = (0..LED_COUNT as u16).collect();
+        Rng::new(seed).shuffle(&mut order);
+
+        Self {
+            id,
+            start: None,
+            progress: 0.0,
+            duration,
+            fade_curve,
+            fade_direction,
+            order,
+            wrapped: Wrapped::new(wrapped),
+        }
+    }
+}
+
+impl Effect for DissolveTransitionEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.wrapped.blend_mode()
+    }
+
+    fn children(&self) -> &[Box<dyn Effect>] {
+        self.wrapped.children()
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        self.wrapped.children_mut()
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let (progress, event) = self.wrapped.update_transition(
+            clock,
+            &mut self.start,
+            self.duration,
+            &self.fade_direction,
+        );
+        self.progress = progress;
+        event
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        let mut t = self.progress;
+        t = self.fade_curve.apply(t);
+        t = self.fade_direction.apply(t);
+
+        // Each pixel fades in over its own slice of the transition.
+        let revealed = t * self.order.len() as f32;
+
+        let order = &self.order;
+        self.wrapped.apply(offset, buffer, pool, |i, wrapped| {
+            wrapped.scale_alpha(revealed - order[offset + i] as f32)
+        });
+    }
+}
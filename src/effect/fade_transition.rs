@@ -1,12 +1,12 @@
 use core::f32;
 
 use alloc::boxed::Box;
-use async_trait::async_trait;
-use embassy_futures::yield_now;
 use embassy_time::{Duration, Instant};
 
-use crate::effect::{DisplayMode, Effect, EffectBuffer, EffectEvent, EffectId};
-use crate::render::{Rgb, LED_COUNT};
+use crate::effect::{
+    BlendMode, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock, Wrapped,
+};
+use crate::render::Rgba;
 
 pub enum FadeDirection {
     In,
@@ -14,7 +14,7 @@ pub enum FadeDirection {
 }
 
 impl FadeDirection {
-    fn apply(&self, x: f32) -> f32 {
+    pub(crate) fn apply(&self, x: f32) -> f32 {
         match self {
             Self::In => x,
             Self::Out => 1.0 - x,
@@ -30,7 +30,7 @@ pub enum FadeCurve {
 }
 
 impl FadeCurve {
-    fn apply(&self, x: f32) -> f32 {
+    pub(crate) fn apply(&self, x: f32) -> f32 {
         match self {
             Self::Linear => x,
             Self::EaseIn => x * x * x,
@@ -42,11 +42,13 @@ impl FadeCurve {
 
 pub struct FadeTransitionEffect {
     id: Option<EffectId>,
-    start: Instant,
+    start: Option<Instant>,
+    /// How far through the transition the effect is, from `0.0` to `1.0`.
+    progress: f32,
     duration: Duration,
     fade_curve: FadeCurve,
     fade_direction: FadeDirection,
-    wrapped: Option<EffectBuffer>,
+    wrapped: Wrapped,
 }
 
 impl FadeTransitionEffect {
@@ -59,16 +61,16 @@ impl FadeTransitionEffect {
     ) -> Self {
         Self {
             id,
-            start: Instant::now(),
+            start: None,
+            progress: 0.0,
             duration: period,
             fade_curve,
             fade_direction,
-            wrapped: wrapped.map(|effect| EffectBuffer::new(effect, LED_COUNT)),
+            wrapped: Wrapped::new(wrapped),
         }
     }
 }
 
-#[async_trait]
 impl Effect for FadeTransitionEffect {
     fn id(&self) -> Option<EffectId> {
         self.id
@@ -78,55 +80,79 @@ impl Effect for FadeTransitionEffect {
         DisplayMode::Blend
     }
 
-    fn update(&mut self, elapsed: Duration) -> Option<EffectEvent> {
-        if let Some(EffectBuffer { effect, .. }) = self.wrapped.as_mut() {
-            match effect.update(elapsed) {
-                Some(EffectEvent::Replace(new_effect)) => {
-                    *effect = new_effect;
-                }
-                Some(EffectEvent::Remove) => {
-                    self.wrapped = None;
-                    return Some(EffectEvent::Remove);
-                }
-                None => (),
-            }
-        }
+    fn blend_mode(&self) -> BlendMode {
+        self.wrapped.blend_mode()
+    }
 
-        if self.start.elapsed() >= self.duration {
-            return Some(match self.wrapped.take() {
-                Some(EffectBuffer { effect, .. }) => match self.fade_direction {
-                    FadeDirection::In => EffectEvent::Replace(effect),
-                    FadeDirection::Out => EffectEvent::Remove,
-                },
-                None => EffectEvent::Remove,
-            });
-        }
+    fn children(&self) -> &[Box<dyn Effect>] {
+        self.wrapped.children()
+    }
 
-        None
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        self.wrapped.children_mut()
     }
 
-    async fn apply(&mut self, buffer: &mut [Rgb]) {
-        if let Some(EffectBuffer { effect, buffer }) = self.wrapped.as_mut() {
-            effect.apply(buffer).await;
-        }
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let (progress, event) = self.wrapped.update_transition(
+            clock,
+            &mut self.start,
+            self.duration,
+            &self.fade_direction,
+        );
+        self.progress = progress;
+        event
+    }
 
-        let mut t = self.start.elapsed().as_micros() as f32 / self.duration.as_micros() as f32;
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        let mut t = self.progress;
         t = self.fade_curve.apply(t);
         t = self.fade_direction.apply(t);
 
-        for (i, pixel) in buffer.iter_mut().enumerate() {
-            let wrapped = self
-                .wrapped
-                .as_ref()
-                .map(|w| w.buffer[i])
-                .unwrap_or(Rgb::BLACK);
-
-            let new_pixel = pixel.lerp(wrapped, t);
-            *pixel = new_pixel;
+        self.wrapped
+            .apply(offset, buffer, pool, |_, wrapped| wrapped.scale_alpha(t));
+    }
+}
 
-            if i % 2 == 0 {
-                yield_now().await;
-            }
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::effect::{BlendMode, LayerEffect};
+    use crate::render::Rgb;
+
+    #[test]
+    fn fading_out_keeps_the_wrapped_blend_mode() {
+        let red_added =
+            LayerEffect::new(None, BlendMode::Add, 1.0, Box::new(Rgb::new(1.0, 0.0, 0.0)));
+        let fade_out = FadeTransitionEffect::new(
+            None,
+            Duration::from_millis(1000),
+            FadeCurve::Linear,
+            FadeDirection::Out,
+            Some(Box::new(red_added)),
+        );
+        let mut stack: Vec<Box<dyn Effect>> =
+            vec![Box::new(Rgb::new(0.0, 0.0, 1.0)), Box::new(fade_out)];
+        let mut pool = BufferPool::new();
+
+        let mut clock = FrameClock::new(Instant::from_millis(0));
+        for ms in (0..1000).step_by(100) {
+            clock.tick(Instant::from_millis(ms));
+            stack.update(&clock);
+
+            let mut buffer = [Rgba::TRANSPARENT; 1];
+            stack.apply(0, &mut buffer, &mut pool);
+
+            // The red is added to the blue beneath it all the way out, rather than covering it.
+            let expected = 1.0 - ms as f32 / 1000.0;
+            assert!(
+                (buffer[0].r - expected).abs() < 1e-3,
+                "{ms}ms: {:?}",
+                buffer[0]
+            );
+            assert!((buffer[0].b - 1.0).abs() < 1e-3, "{ms}ms: {:?}", buffer[0]);
         }
     }
 }
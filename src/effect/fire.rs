@@ -0,0 +1,107 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use embassy_time::Duration;
+
+use crate::effect::{BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock, Rng};
+use crate::render::{Rgb, Rgba, LED_COUNT};
+
+/// How often the heat simulation advances, independent of the frame rate.
+const STEP: Duration = Duration::from_millis(16);
+/// Caps the work done in one update if a frame took unusually long.
+const MAX_STEPS_PER_UPDATE: u32 = 4;
+/// New sparks ignite somewhere in this many pixels at the base of the fire.
+const SPARK_HEIGHT: u32 = 7;
+
+/// A heat-diffusion fire that rises from the first pixel towards the last.
+pub struct FireEffect {
+    id: Option<EffectId>,
+    rng: Rng,
+    /// How much heat is lost each step. Higher values make shorter flames.
+    cooling: u8,
+    /// The chance, out of 255, that a new spark ignites each step. Higher values make a
+    /// more active fire.
+    sparking: u8,
+    heat: Vec<u8>,
+    accumulated: Duration,
+}
+
+impl FireEffect {
+    pub fn new(id: Option<EffectId>, seed: u32, cooling: u8, sparking: u8) -> Self {
+        Self {
+            id,
+            rng: Rng::new(seed),
+            cooling,
+            sparking,
+            heat: vec![0; LED_COUNT],
+            accumulated: Duration::from_ticks(0),
+        }
+    }
+
+    fn step(&mut self) {
+        let len = self.heat.len();
+
+        // Cool down every cell a little.
+        let max_cooling = self.cooling as u32 * 10 / len as u32 + 2;
+        for heat in self.heat.iter_mut() {
+            *heat = heat.saturating_sub(self.rng.below(max_cooling) as u8);
+        }
+
+        // Heat drifts up and diffuses a little.
+        for i in (2..len).rev() {
+            self.heat[i] = ((self.heat[i - 1] as u16 + 2 * self.heat[i - 2] as u16) / 3) as u8;
+        }
+
+        // Randomly ignite new sparks near the bottom.
+        if self.rng.below(255) < self.sparking as u32 {
+            let i = self.rng.below(SPARK_HEIGHT.min(len as u32)) as usize;
+            self.heat[i] = self.heat[i].saturating_add(160 + self.rng.below(96) as u8);
+        }
+    }
+}
+
+/// Maps a heat value onto a black, red, yellow, white palette.
+fn heat_color(heat: u8) -> Rgb {
+    // Scale down to 0..=191 so that the palette divides evenly into thirds.
+    let t = (heat as u16 * 191 / 255) as u8;
+    let ramp = (t & 0x3f) << 2;
+
+    if t & 0x80 != 0 {
+        Rgb::from_u8(255, 255, ramp)
+    } else if t & 0x40 != 0 {
+        Rgb::from_u8(255, ramp, 0)
+    } else {
+        Rgb::from_u8(ramp, 0, 0)
+    }
+}
+
+impl Effect for FireEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Opaque
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        self.accumulated += clock.elapsed;
+
+        let mut steps = 0;
+        while self.accumulated >= STEP {
+            self.accumulated -= STEP;
+
+            if steps < MAX_STEPS_PER_UPDATE {
+                self.step();
+                steps += 1;
+            }
+        }
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], _pool: &mut BufferPool) {
+        for (pixel, &heat) in buffer.iter_mut().zip(&self.heat[offset..]) {
+            *pixel = heat_color(heat).into();
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use embassy_time::{Duration, Instant};
+use micromath::F32Ext;
+
+use crate::effect::{BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock};
+use crate::render::{Palette, Rgba, LED_COUNT};
+
+/// Lays a palette across the strip, optionally scrolling it along.
+///
+/// While scrolling, the end of the palette meets its start, so palettes whose last stop matches
+/// their first scroll without a seam.
+pub struct GradientEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// How far the palette has scrolled, as a fraction of the strip.
+    offset: f32,
+    palette: Palette,
+    /// The time it takes the palette to scroll the length of the strip, if it scrolls at all.
+    scroll_period: Option<Duration>,
+}
+
+impl GradientEffect {
+    pub fn new(id: Option<EffectId>, palette: Palette, scroll_period: Option<Duration>) -> Self {
+        Self {
+            id,
+            start: None,
+            offset: 0.0,
+            palette,
+            scroll_period,
+        }
+    }
+}
+
+impl Effect for GradientEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Opaque
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let start = *self.start.get_or_insert(clock.now);
+        if let Some(period) = self.scroll_period {
+            self.offset = clock.phase(start, period);
+        }
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], _pool: &mut BufferPool) {
+        for (pixel, i) in buffer.iter_mut().zip(offset..) {
+            let mut x = i as f32 / LED_COUNT as f32;
+            if self.scroll_period.is_some() {
+                x = (x - self.offset).rem_euclid(1.0);
+            }
+
+            *pixel = self.palette.sample(x).into();
+        }
+    }
+}
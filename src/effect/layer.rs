@@ -0,0 +1,80 @@
+use alloc::boxed::Box;
+use core::slice;
+
+use crate::effect::{
+    BlendMode, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock,
+};
+use crate::render::Rgba;
+
+/// Sets the blend mode and opacity that an effect stack uses to composite the wrapped effect.
+pub struct LayerEffect {
+    id: Option<EffectId>,
+    blend_mode: BlendMode,
+    opacity: f32,
+    wrapped: Box<dyn Effect>,
+}
+
+impl LayerEffect {
+    pub fn new(
+        id: Option<EffectId>,
+        blend_mode: BlendMode,
+        opacity: f32,
+        wrapped: Box<dyn Effect>,
+    ) -> Self {
+        Self {
+            id,
+            blend_mode,
+            opacity: opacity.clamp(0.0, 1.0),
+            wrapped,
+        }
+    }
+}
+
+impl Effect for LayerEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        if self.opacity < 1.0 {
+            DisplayMode::Blend
+        } else {
+            self.wrapped.display_mode()
+        }
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn children(&self) -> &[Box<dyn Effect>] {
+        slice::from_ref(&self.wrapped)
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        slice::from_mut(&mut self.wrapped)
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        match self.wrapped.update(clock) {
+            Some(EffectEvent::Replace(new_effect)) => {
+                self.wrapped = new_effect;
+                None
+            }
+            Some(EffectEvent::Remove) => Some(EffectEvent::Remove),
+            None => None,
+        }
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        self.wrapped.apply(offset, buffer, pool);
+
+        if self.opacity >= 1.0 {
+            return;
+        }
+
+        for pixel in buffer.iter_mut() {
+            *pixel = pixel.scale_alpha(self.opacity);
+        }
+    }
+}
@@ -1,10 +1,9 @@
 use alloc::boxed::Box;
-use alloc::vec;
 use alloc::vec::Vec;
-use async_trait::async_trait;
-use embassy_time::Duration;
+use core::any::Any;
+use core::mem;
 
-use crate::render::Rgb;
+use crate::render::{Rgb, Rgba};
 
 /*
 pub use self::sine_pulse::SinePulseEffect;
@@ -13,11 +12,57 @@ pub use self::solid::SolidEffect;
 mod sine_pulse;
 mod solid; */
 
+pub use self::clock::FrameClock;
+pub use self::comet::{CometBoundary, CometDirection, CometEffect};
+pub use self::dissolve_transition::DissolveTransitionEffect;
 pub use self::fade_transition::{FadeCurve, FadeDirection, FadeTransitionEffect};
-pub use self::sine_pulse::SinePulseEffect;
+pub use self::fire::FireEffect;
+pub use self::gradient::GradientEffect;
+pub use self::layer::LayerEffect;
+pub use self::modulator::{ModulatorEffect, Waveform};
+pub use self::noise::Noise;
+pub use self::plasma::PlasmaEffect;
+pub use self::playlist::{PlaylistEffect, PlaylistTransition};
+pub use self::pool::{BufferPool, Layer};
+pub use self::rainbow_cycle::RainbowCycleEffect;
+pub use self::rng::Rng;
+pub use self::scanner::ScannerEffect;
+pub use self::stack::EffectStack;
+pub use self::strobe::{StrobeEffect, MAX_STROBE_CONTRAST, MAX_STROBE_FREQUENCY};
+pub use self::time_scale::TimeScaleEffect;
+pub use self::timeline::{Keyframe, Playback, TimelineEffect};
+pub use self::twinkle::TwinkleEffect;
+pub use self::wipe_transition::{WipeOrigin, WipeTransitionEffect};
+use self::wrapped::Wrapped;
 
+mod clock;
+mod comet;
+mod dissolve_transition;
 mod fade_transition;
-mod sine_pulse;
+mod fire;
+mod gradient;
+mod layer;
+mod modulator;
+mod noise;
+mod plasma;
+mod playlist;
+mod pool;
+mod rainbow_cycle;
+mod rng;
+mod scanner;
+mod stack;
+mod strobe;
+mod time_scale;
+mod timeline;
+mod twinkle;
+mod wipe_transition;
+mod wrapped;
+
+/// The most pixels that an effect is asked to draw at once.
+///
+/// Effects draw synchronously, so the renderer splits each frame into chunks of this many pixels
+/// and yields to other tasks between them.
+pub const CHUNK_SIZE: usize = 8;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct EffectId(pub u32);
@@ -28,23 +73,156 @@ pub enum DisplayMode {
     Opaque,
 }
 
+/// How an effect stack combines an effect's layer with the effects beneath it.
+///
+/// Whatever the mode, the layer's alpha controls how much of the result shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    /// The layer covers the effects beneath it.
+    Normal,
+    /// The layer is added to the effects beneath it.
+    Add,
+    /// The layer is multiplied with the effects beneath it.
+    Multiply,
+    /// The layer brightens the effects beneath it.
+    Screen,
+    /// The brightest of the layer and the effects beneath it is taken, per channel.
+    Lighten,
+}
+
+impl BlendMode {
+    /// Combines a pixel from an effect's layer with the pixel beneath it.
+    pub fn blend(&self, base: Rgb, layer: Rgb) -> Rgb {
+        let per_channel = |f: fn(f32, f32) -> f32| {
+            Rgb::new(f(base.r, layer.r), f(base.g, layer.g), f(base.b, layer.b))
+        };
+
+        match self {
+            Self::Normal => layer,
+            Self::Add => per_channel(|b, l| (b + l).min(1.0)),
+            Self::Multiply => per_channel(|b, l| b * l),
+            Self::Screen => per_channel(|b, l| 1.0 - (1.0 - b) * (1.0 - l)),
+            Self::Lighten => per_channel(f32::max),
+        }
+    }
+
+    /// Composites a pixel from an effect's layer over the pixel beneath it.
+    pub fn composite(&self, below: Rgba, above: Rgba) -> Rgba {
+        if above.a <= 0.0 {
+            return below;
+        }
+        if below.a <= 0.0 {
+            return above;
+        }
+
+        // Where the pixel beneath is transparent, there's nothing to blend with, so the layer's
+        // own color shows through instead.
+        let blended = self.blend(below.rgb(), above.rgb());
+        let source = above.rgb().lerp(blended, below.a);
+
+        let a = above.a + below.a * (1.0 - above.a);
+        Rgba::from_rgb(below.rgb().lerp(source, above.a / a), a)
+    }
+}
+
 pub enum EffectEvent {
     Replace(Box<dyn Effect>),
     Remove,
 }
 
-#[async_trait]
-pub trait Effect: Send + Sync {
+pub trait Effect: AsAny + Send + Sync {
     fn id(&self) -> Option<EffectId>;
     fn display_mode(&self) -> DisplayMode;
-    fn update(&mut self, elapsed: Duration) -> Option<EffectEvent>;
-    async fn apply(&mut self, buffer: &mut [Rgb]);
+
+    /// Effects are drawn directly over the effects beneath them unless they say otherwise.
+    fn blend_mode(&self) -> BlendMode {
+        BlendMode::Normal
+    }
+
+    /// The effects that this effect wraps, if any.
+    fn children(&self) -> &[Box<dyn Effect>] {
+        &[]
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        &mut []
+    }
+
+    /// Takes the child at `index` out of this effect. Effects that can't do without a child
+    /// are left wrapping an empty stack instead.
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Effect>> {
+        self.children_mut()
+            .get_mut(index)
+            .map(|child| mem::replace(child, empty()))
+    }
+
+    /// Moves the child at `from` to `to`, shifting the children in between. Returns whether it
+    /// was moved, which it isn't if either index is out of bounds or the effect's children have
+    /// an order of their own.
+    fn move_child(&mut self, from: usize, to: usize) -> bool {
+        let children = self.children_mut();
+        if from >= children.len() || to >= children.len() {
+            return false;
+        }
+
+        if from < to {
+            children[from..=to].rotate_left(1);
+        } else {
+            children[to..=from].rotate_right(1);
+        }
+
+        true
+    }
+
+    /// How many times a second this effect, or anything it wraps, flashes, if it flashes at all.
+    ///
+    /// Effects that play their children faster than real time use this to keep them under
+    /// [`MAX_STROBE_FREQUENCY`].
+    fn flash_frequency(&self) -> Option<f32> {
+        self.children()
+            .iter()
+            .filter_map(|child| child.flash_frequency())
+            .reduce(f32::max)
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent>;
+
+    /// Draws the effect's layer into `buffer`, overwriting every pixel. `buffer` holds the part
+    /// of the strip starting at pixel `offset`, and is never longer than [`CHUNK_SIZE`].
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool);
+}
+
+/// Lets an effect be viewed as its concrete type, for controlling it after it's been added to a
+/// stack. Implemented for every effect.
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl dyn Effect + '_ {
+    /// Returns the effect as a `T`, if that's what it is.
+    pub fn downcast_ref<T: Effect + 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Effect + 'static>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
 }
 
 mod core_implementations {
     use super::*;
 
-    #[async_trait]
     impl Effect for Box<dyn Effect> {
         fn id(&self) -> Option<EffectId> {
             self.as_ref().id()
@@ -54,48 +232,79 @@ mod core_implementations {
             self.as_ref().display_mode()
         }
 
-        fn update(&mut self, elapsed: Duration) -> Option<EffectEvent> {
-            self.as_mut().update(elapsed)
+        fn blend_mode(&self) -> BlendMode {
+            self.as_ref().blend_mode()
+        }
+
+        fn children(&self) -> &[Box<dyn Effect>] {
+            self.as_ref().children()
+        }
+
+        fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+            self.as_mut().children_mut()
         }
 
-        async fn apply(&mut self, buffer: &mut [Rgb]) {
-            self.as_mut().apply(buffer).await
+        fn remove_child(&mut self, index: usize) -> Option<Box<dyn Effect>> {
+            self.as_mut().remove_child(index)
+        }
+
+        fn move_child(&mut self, from: usize, to: usize) -> bool {
+            self.as_mut().move_child(from, to)
+        }
+
+        fn flash_frequency(&self) -> Option<f32> {
+            self.as_ref().flash_frequency()
+        }
+
+        fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+            self.as_mut().update(clock)
+        }
+
+        fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+            self.as_mut().apply(offset, buffer, pool)
         }
     }
 
-    #[async_trait]
     impl Effect for Vec<Box<dyn Effect>> {
         fn id(&self) -> Option<EffectId> {
             None
         }
 
         fn display_mode(&self) -> DisplayMode {
-            self.iter()
-                .map(Effect::display_mode)
-                .find(|&mode| mode == DisplayMode::Opaque)
-                .unwrap_or(DisplayMode::Blend)
+            match self.iter().any(|effect| covers(effect.as_ref())) {
+                true => DisplayMode::Opaque,
+                false => DisplayMode::Blend,
+            }
+        }
+
+        fn children(&self) -> &[Box<dyn Effect>] {
+            self
         }
 
-        fn update(&mut self, elapsed: Duration) -> Option<EffectEvent> {
-            let mut removals = Vec::with_capacity(self.len());
+        fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+            self
+        }
 
-            for (i, effect) in self.iter_mut().enumerate() {
-                match effect.update(elapsed) {
-                    Some(EffectEvent::Replace(new_effect)) => *effect = new_effect,
-                    Some(EffectEvent::Remove) => removals.push(i),
-                    None => (),
-                }
-            }
+        fn remove_child(&mut self, index: usize) -> Option<Box<dyn Effect>> {
+            (index < self.len()).then(|| self.remove(index))
+        }
 
-            for i in removals.into_iter().rev() {
-                self.remove(i);
-            }
+        fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+            self.retain_mut(|effect| match effect.update(clock) {
+                Some(EffectEvent::Replace(new_effect)) => {
+                    *effect = new_effect;
+                    true
+                }
+                Some(EffectEvent::Remove) => false,
+                None => true,
+            });
 
             None
         }
 
-        async fn apply(&mut self, buffer: &mut [Rgb]) {
+        fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
             if self.is_empty() {
+                buffer.fill(Rgba::TRANSPARENT);
                 return;
             }
 
@@ -106,16 +315,39 @@ mod core_implementations {
                 - self
                     .iter()
                     .rev()
-                    .position(|effect| effect.display_mode() == DisplayMode::Opaque)
+                    .position(|effect| covers(effect.as_ref()))
                     .unwrap_or(last);
 
-            for effect in self.iter_mut().take(last + 1).skip(first) {
-                effect.apply(buffer).await;
+            let mut effects = self.iter_mut().take(last + 1).skip(first);
+
+            // The bottom effect has nothing beneath it to blend with, so it can draw straight into
+            // the buffer.
+            if let Some(effect) = effects.next() {
+                effect.apply(offset, buffer, pool);
+            }
+
+            // Every other effect draws into its own layer, which is composited over the buffer.
+            let mut layer = pool.take();
+
+            for effect in effects {
+                let layer = &mut layer[..buffer.len()];
+                effect.apply(offset, layer, pool);
+
+                let blend_mode = effect.blend_mode();
+                for (pixel, &layer_pixel) in buffer.iter_mut().zip(layer.iter()) {
+                    *pixel = blend_mode.composite(*pixel, layer_pixel);
+                }
             }
+
+            pool.give(layer);
         }
     }
 
-    #[async_trait]
+    /// Whether an effect completely hides everything beneath it in a stack.
+    fn covers(effect: &dyn Effect) -> bool {
+        effect.display_mode() == DisplayMode::Opaque && effect.blend_mode() == BlendMode::Normal
+    }
+
     impl Effect for Rgb {
         fn id(&self) -> Option<EffectId> {
             None
@@ -125,26 +357,17 @@ mod core_implementations {
             DisplayMode::Opaque
         }
 
-        fn update(&mut self, _elapsed: Duration) -> Option<EffectEvent> {
+        fn update(&mut self, _clock: &FrameClock) -> Option<EffectEvent> {
             None
         }
 
-        async fn apply(&mut self, buffer: &mut [Rgb]) {
-            buffer.fill(*self);
+        fn apply(&mut self, _offset: usize, buffer: &mut [Rgba], _pool: &mut BufferPool) {
+            buffer.fill((*self).into());
         }
     }
 }
 
-struct EffectBuffer {
-    effect: Box<dyn Effect>,
-    buffer: Vec<Rgb>,
-}
-
-impl EffectBuffer {
-    fn new(effect: Box<dyn Effect>, size: usize) -> Self {
-        Self {
-            effect,
-            buffer: vec![Rgb::BLACK; size],
-        }
-    }
+/// An effect that draws nothing, for filling the place of a removed effect.
+fn empty() -> Box<dyn Effect> {
+    Box::new(Vec::<Box<dyn Effect>>::new())
 }
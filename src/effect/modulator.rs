@@ -0,0 +1,218 @@
+use core::f32;
+
+use alloc::boxed::Box;
+use embassy_time::{Duration, Instant};
+use log::warn;
+use micromath::F32Ext;
+
+use crate::effect::{
+    BlendMode, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FadeCurve, FrameClock, Rng,
+    Wrapped, MAX_STROBE_FREQUENCY,
+};
+use crate::render::Rgba;
+
+/// The shape that a [`ModulatorEffect`] follows over each period, between `-1.0` and `1.0`.
+pub enum Waveform {
+    Sine,
+    Triangle,
+    /// High for the first `duty_cycle` of each period, and low for the rest.
+    Square {
+        duty_cycle: f32,
+    },
+    /// Rises steadily over each period, then drops straight back down.
+    Saw,
+    /// Holds a new random value for each period.
+    Random {
+        seed: u32,
+    },
+    /// Glides from one random value to the next over each period.
+    SmoothRandom {
+        seed: u32,
+    },
+}
+
+impl Waveform {
+    /// The value of the waveform `t` of the way through a period. The random waveforms move from
+    /// `held` towards `next`.
+    fn sample(&self, t: f32, held: f32, next: f32) -> f32 {
+        match self {
+            Self::Sine => (2.0 * f32::consts::PI * t).sin(),
+            // Starts at zero and rises, to line up with the sine.
+            Self::Triangle => 1.0 - 4.0 * ((t + 0.25).rem_euclid(1.0) - 0.5).abs(),
+            Self::Square { duty_cycle } => {
+                if t < *duty_cycle {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Self::Saw => 2.0 * t - 1.0,
+            Self::Random { .. } => held,
+            Self::SmoothRandom { .. } => held + (next - held) * FadeCurve::Smoothstep.apply(t),
+        }
+    }
+
+    /// Whether the waveform jumps between values rather than moving smoothly, which flashes the
+    /// wrapped effect once a period.
+    fn is_hard_edged(&self) -> bool {
+        matches!(self, Self::Square { .. } | Self::Saw | Self::Random { .. })
+    }
+
+    fn seed(&self) -> u32 {
+        match self {
+            Self::Random { seed } | Self::SmoothRandom { seed } => *seed,
+            _ => 0,
+        }
+    }
+}
+
+/// Modulates the opacity of the wrapped effect along a repeating waveform.
+///
+/// The opacity is the waveform scaled by `amplitude` and shifted by `offset`, so an amplitude and
+/// offset of `0.5` swing the wrapped effect between fully transparent and fully opaque.
+///
+/// Waveforms that jump between values flash the wrapped effect, so their period is held to at
+/// least one over [`MAX_STROBE_FREQUENCY`].
+pub struct ModulatorEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// How far through the current period the effect is, from `0.0` to `1.0`.
+    position: f32,
+    /// The number of periods completed so far, for knowing when to pick new random values.
+    cycle: u64,
+    waveform: Waveform,
+    period: Duration,
+    amplitude: f32,
+    offset: f32,
+    /// How far through its period the waveform starts, from `0.0` to `1.0`.
+    phase: f32,
+    rng: Rng,
+    held: f32,
+    next: f32,
+    wrapped: Wrapped,
+}
+
+impl ModulatorEffect {
+    pub fn new(
+        id: Option<EffectId>,
+        waveform: Waveform,
+        period: Duration,
+        amplitude: f32,
+        offset: f32,
+        phase: f32,
+        wrapped: Option<Box<dyn Effect>>,
+    ) -> Self {
+        // Rounded up, so that the flash rate never comes out above the maximum.
+        let min_period = Duration::from_micros((1_000_000.0 / MAX_STROBE_FREQUENCY).ceil() as u64);
+        let period = if waveform.is_hard_edged() && period < min_period {
+            warn!(
+                "Modulator period of {} ms clamped to {} ms.",
+                period.as_millis(),
+                min_period.as_millis(),
+            );
+            min_period
+        } else {
+            period
+        };
+
+        let mut rng = Rng::new(waveform.seed());
+        let held = random(&mut rng);
+        let next = random(&mut rng);
+
+        Self {
+            id,
+            start: None,
+            position: 0.0,
+            cycle: 0,
+            waveform,
+            period,
+            amplitude,
+            offset,
+            phase: phase.rem_euclid(1.0),
+            rng,
+            held,
+            next,
+            wrapped: Wrapped::new(wrapped),
+        }
+    }
+
+    /// Pulses the wrapped effect along a sine wave.
+    pub fn sine_pulse(
+        id: Option<EffectId>,
+        period: Duration,
+        amplitude: f32,
+        offset: f32,
+        wrapped: Option<Box<dyn Effect>>,
+    ) -> Self {
+        Self::new(id, Waveform::Sine, period, amplitude, offset, 0.0, wrapped)
+    }
+}
+
+/// Returns a value in the range `-1.0..1.0`.
+fn random(rng: &mut Rng) -> f32 {
+    rng.next_f32() * 2.0 - 1.0
+}
+
+impl Effect for ModulatorEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.wrapped.blend_mode()
+    }
+
+    fn children(&self) -> &[Box<dyn Effect>] {
+        self.wrapped.children()
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        self.wrapped.children_mut()
+    }
+
+    fn flash_frequency(&self) -> Option<f32> {
+        let own = self
+            .waveform
+            .is_hard_edged()
+            .then(|| 1_000_000.0 / self.period.as_micros() as f32);
+        let wrapped = self
+            .wrapped
+            .children()
+            .iter()
+            .filter_map(|child| child.flash_frequency());
+
+        own.into_iter().chain(wrapped).reduce(f32::max)
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        if let Some(event) = self.wrapped.update(clock) {
+            return Some(event);
+        }
+
+        let start = *self.start.get_or_insert(clock.now);
+        let position = clock.phase(start, self.period) + self.phase;
+        let wrapped_around = position.floor();
+        self.position = position - wrapped_around;
+
+        let cycle = clock.cycles(start, self.period) + wrapped_around as u64;
+        if cycle != self.cycle {
+            self.cycle = cycle;
+            self.held = self.next;
+            self.next = random(&mut self.rng);
+        }
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        let t = self.waveform.sample(self.position, self.held, self.next);
+        let a = t * self.amplitude + self.offset;
+
+        self.wrapped
+            .apply(offset, buffer, pool, |_, wrapped| wrapped.scale_alpha(a));
+    }
+}
@@ -0,0 +1,130 @@
+use micromath::F32Ext;
+
+use crate::effect::Rng;
+
+/// Seedable gradient (Perlin) noise in one and two dimensions.
+///
+/// The noise repeats every 256 units along each axis, and ranges over roughly `-1.0..=1.0`.
+#[derive(Clone)]
+pub struct Noise {
+    permutation: [u8; 256],
+}
+
+impl Noise {
+    pub fn new(seed: u32) -> Self {
+        let mut permutation = [0; 256];
+        for (i, p) in permutation.iter_mut().enumerate() {
+            *p = i as u8;
+        }
+
+        Rng::new(seed).shuffle(&mut permutation);
+
+        Self { permutation }
+    }
+
+    pub fn noise1(&self, x: f32) -> f32 {
+        let x0 = x.floor();
+        let xf = x - x0;
+        let xi = x0 as i32;
+
+        let a = grad1(self.hash(xi), xf);
+        let b = grad1(self.hash(xi + 1), xf - 1.0);
+
+        // The 1D noise only reaches about half of the range, so scale it up to match 2D.
+        (lerp(a, b, fade(xf)) * 2.0).clamp(-1.0, 1.0)
+    }
+
+    pub fn noise2(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (xf, yf) = (x - x0, y - y0);
+        let (xi, yi) = (x0 as i32, y0 as i32);
+
+        let hash = |dx: i32, dy: i32| self.hash(self.hash(xi + dx) as i32 + yi + dy);
+
+        let aa = grad2(hash(0, 0), xf, yf);
+        let ba = grad2(hash(1, 0), xf - 1.0, yf);
+        let ab = grad2(hash(0, 1), xf, yf - 1.0);
+        let bb = grad2(hash(1, 1), xf - 1.0, yf - 1.0);
+
+        let u = fade(xf);
+        let v = fade(yf);
+
+        lerp(lerp(aa, ba, u), lerp(ab, bb, u), v).clamp(-1.0, 1.0)
+    }
+
+    /// Sums `octaves` layers of 2D noise, each at twice the frequency and half the amplitude of
+    /// the last, for a more detailed result in the same range.
+    pub fn fbm2(&self, x: f32, y: f32, octaves: u32) -> f32 {
+        let mut sum = 0.0;
+        let mut total_amplitude = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+
+        for _ in 0..octaves.max(1) {
+            sum += self.noise2(x * frequency, y * frequency) * amplitude;
+            total_amplitude += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+
+        sum / total_amplitude
+    }
+
+    fn hash(&self, i: i32) -> u8 {
+        self.permutation[(i & 0xff) as usize]
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn grad1(hash: u8, x: f32) -> f32 {
+    // A gradient between -1.0 and 1.0, never zero.
+    let gradient = ((hash & 0x0f) as f32 + 1.0) / 16.0;
+    if hash & 0x10 != 0 {
+        -gradient * x
+    } else {
+        gradient * x
+    }
+}
+
+fn grad2(hash: u8, x: f32, y: f32) -> f32 {
+    match hash & 0x07 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise1_spans_its_range_without_saturating() {
+        let noise = Noise::new(3);
+        let samples = (0..25_600).map(|i| noise.noise1(i as f32 * 0.01));
+
+        let (mut min, mut max, mut saturated) = (0.0f32, 0.0f32, 0);
+        for value in samples {
+            min = min.min(value);
+            max = max.max(value);
+            if value.abs() >= 1.0 {
+                saturated += 1;
+            }
+        }
+
+        assert_eq!(saturated, 0);
+        assert!(min < -0.5 && max > 0.5, "{min}..{max}");
+    }
+}
@@ -0,0 +1,65 @@
+use embassy_time::{Duration, Instant};
+
+use crate::effect::{BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock, Noise};
+use crate::render::{Palette, Rgba};
+
+/// The number of noise layers summed for each pixel.
+const OCTAVES: u32 = 2;
+/// The noise repeats after this many units, so time can wrap around without a visible jump.
+const NOISE_PERIOD: f32 = 256.0;
+
+/// Slowly evolving, organic color driven by noise over space and time.
+pub struct PlasmaEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// The position of the current frame along the noise's time axis.
+    z: f32,
+    noise: Noise,
+    palette: Palette,
+    /// Noise units per pixel. Smaller values make larger, smoother blobs.
+    scale: f32,
+    /// Noise units per second.
+    speed: f32,
+}
+
+impl PlasmaEffect {
+    pub fn new(id: Option<EffectId>, seed: u32, palette: Palette, scale: f32, speed: f32) -> Self {
+        Self {
+            id,
+            start: None,
+            z: 0.0,
+            noise: Noise::new(seed),
+            palette,
+            scale,
+            speed,
+        }
+    }
+
+    fn period(&self) -> Duration {
+        Duration::from_micros((NOISE_PERIOD / self.speed * 1_000_000.0) as u64)
+    }
+}
+
+impl Effect for PlasmaEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Opaque
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let start = *self.start.get_or_insert(clock.now);
+        self.z = clock.phase(start, self.period()) * NOISE_PERIOD;
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], _pool: &mut BufferPool) {
+        for (pixel, i) in buffer.iter_mut().zip(offset..) {
+            let n = self.noise.fbm2(i as f32 * self.scale, self.z, OCTAVES);
+            *pixel = self.palette.sample(n * 0.5 + 0.5).into();
+        }
+    }
+}
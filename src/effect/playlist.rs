@@ -0,0 +1,296 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use embassy_time::{Duration, Instant};
+
+use crate::effect::{
+    empty, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FadeCurve, FrameClock, Rng,
+};
+use crate::render::Rgba;
+
+/// How a [`PlaylistEffect`] moves from one scene to the next.
+pub enum PlaylistTransition {
+    /// Switches straight to the next scene.
+    Cut,
+    /// Crossfades into the next scene over `duration`.
+    Fade {
+        duration: Duration,
+        curve: FadeCurve,
+    },
+}
+
+/// Moves the playlist will make on its next update.
+enum Skip {
+    Next,
+    Previous,
+}
+
+/// Shows a list of scenes one at a time, each for its own dwell time.
+///
+/// Scenes are only updated while they're showing, so each one starts from its beginning the
+/// first time it's shown and carries on from there when it comes back around.
+pub struct PlaylistEffect {
+    id: Option<EffectId>,
+    scenes: Vec<Box<dyn Effect>>,
+    dwell_times: Vec<Duration>,
+    transition: PlaylistTransition,
+    /// Reshuffles the order of the scenes before each pass through them, if shuffling.
+    shuffle: Option<Rng>,
+    /// The order the scenes are played in, as indices into `scenes`.
+    order: Vec<usize>,
+    /// Where the current scene is in `order`.
+    position: usize,
+    /// When the current scene started showing.
+    scene_start: Option<Instant>,
+    /// The scene being faded out, if a fade is in progress.
+    outgoing: Option<usize>,
+    /// How far the fade into the current scene is, after easing, from `0.0` to `1.0`.
+    mix: f32,
+    skip: Option<Skip>,
+}
+
+impl PlaylistEffect {
+    /// Creates a playlist from `(scene, dwell time)` pairs, played in order unless `shuffle_seed`
+    /// is given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scenes` is empty.
+    pub fn new(
+        id: Option<EffectId>,
+        scenes: Vec<(Box<dyn Effect>, Duration)>,
+        transition: PlaylistTransition,
+        shuffle_seed: Option<u32>,
+    ) -> Self {
+        assert!(!scenes.is_empty(), "a playlist needs at least one scene");
+
+        let (scenes, dwell_times): (Vec<_>, Vec<_>) = scenes.into_iter().unzip();
+        let mut order: Vec<usize> = (0..scenes.len()).collect();
+        let mut shuffle = shuffle_seed.map(Rng::new);
+        if let Some(rng) = shuffle.as_mut() {
+            rng.shuffle(&mut order);
+        }
+
+        Self {
+            id,
+            scenes,
+            dwell_times,
+            transition,
+            shuffle,
+            order,
+            position: 0,
+            scene_start: None,
+            outgoing: None,
+            mix: 1.0,
+            skip: None,
+        }
+    }
+
+    /// Moves on to the next scene.
+    pub fn next(&mut self) {
+        self.skip = Some(Skip::Next);
+    }
+
+    /// Goes back to the scene before the current one.
+    pub fn previous(&mut self) {
+        self.skip = Some(Skip::Previous);
+    }
+
+    /// The index of the scene that's showing, or being faded into.
+    pub fn current(&self) -> usize {
+        self.order[self.position]
+    }
+
+    fn advance(&mut self, skip: Skip, now: Instant) {
+        let outgoing = self.current();
+
+        match skip {
+            Skip::Next if self.position + 1 < self.order.len() => self.position += 1,
+            Skip::Next => {
+                self.position = 0;
+
+                if let Some(rng) = self.shuffle.as_mut() {
+                    rng.shuffle(&mut self.order);
+
+                    // Don't show the same scene twice in a row across passes.
+                    if self.order.len() > 1 && self.order[0] == outgoing {
+                        self.order.swap(0, 1);
+                    }
+                }
+            }
+            Skip::Previous if self.position > 0 => self.position -= 1,
+            Skip::Previous => self.position = self.order.len() - 1,
+        }
+
+        self.scene_start = Some(now);
+        self.outgoing = match self.transition {
+            PlaylistTransition::Fade { .. } if outgoing != self.current() => Some(outgoing),
+            _ => None,
+        };
+    }
+}
+
+/// Where the scene at `index` ends up after the scene at `from` is moved to `to`.
+fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < index && index <= to {
+        index - 1
+    } else if to <= index && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
+/// Updates a scene, leaving an empty scene in its place if it ends.
+fn update_scene(scene: &mut Box<dyn Effect>, clock: &FrameClock) {
+    match scene.update(clock) {
+        Some(EffectEvent::Replace(new_scene)) => *scene = new_scene,
+        Some(EffectEvent::Remove) => *scene = empty(),
+        None => (),
+    }
+}
+
+impl Effect for PlaylistEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn children(&self) -> &[Box<dyn Effect>] {
+        &self.scenes
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        &mut self.scenes
+    }
+
+    /// Moves a scene along with its dwell time. The order the scenes are played in follows them,
+    /// so the same scenes play in the same order as before.
+    fn move_child(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.scenes.len() || to >= self.scenes.len() {
+            return false;
+        }
+
+        if from < to {
+            self.scenes[from..=to].rotate_left(1);
+            self.dwell_times[from..=to].rotate_left(1);
+        } else {
+            self.scenes[to..=from].rotate_right(1);
+            self.dwell_times[to..=from].rotate_right(1);
+        }
+
+        for index in self.order.iter_mut() {
+            *index = moved_index(*index, from, to);
+        }
+        if let Some(outgoing) = self.outgoing.as_mut() {
+            *outgoing = moved_index(*outgoing, from, to);
+        }
+
+        true
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let scene_start = *self.scene_start.get_or_insert(clock.now);
+
+        let skip = match self.skip.take() {
+            Some(skip) => Some(skip),
+            None if clock.since(scene_start) >= self.dwell_times[self.current()] => {
+                Some(Skip::Next)
+            }
+            None => None,
+        };
+        if let Some(skip) = skip {
+            self.advance(skip, clock.now);
+        }
+
+        let current = self.current();
+        update_scene(&mut self.scenes[current], clock);
+
+        if let Some(outgoing) = self.outgoing {
+            update_scene(&mut self.scenes[outgoing], clock);
+
+            if let PlaylistTransition::Fade { duration, curve } = &self.transition {
+                let start = self.scene_start.unwrap_or(clock.now);
+                if clock.since(start) >= *duration {
+                    self.outgoing = None;
+                } else {
+                    self.mix = curve.apply(clock.progress(start, *duration));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        let current = self.current();
+
+        let Some(outgoing) = self.outgoing else {
+            self.scenes[current].apply(offset, buffer, pool);
+            return;
+        };
+
+        self.scenes[outgoing].apply(offset, buffer, pool);
+
+        let mut layer = pool.take();
+        let incoming = &mut layer[..buffer.len()];
+        self.scenes[current].apply(offset, incoming, pool);
+
+        for (pixel, &incoming) in buffer.iter_mut().zip(incoming.iter()) {
+            *pixel = pixel.lerp(incoming, self.mix);
+        }
+
+        pool.give(layer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::effect::{BlendMode, EffectStack, LayerEffect};
+    use crate::render::Rgb;
+
+    fn scene(id: u32, dwell_ms: u64) -> (Box<dyn Effect>, Duration) {
+        let effect = LayerEffect::new(
+            Some(EffectId(id)),
+            BlendMode::Normal,
+            1.0,
+            Box::new(Rgb::WHITE),
+        );
+        (Box::new(effect), Duration::from_millis(dwell_ms))
+    }
+
+    fn showing(stack: &[Box<dyn Effect>]) -> Option<EffectId> {
+        let playlist = stack[0].downcast_ref::<PlaylistEffect>().unwrap();
+        playlist.scenes[playlist.current()].id()
+    }
+
+    #[test]
+    fn moved_scenes_keep_their_dwell_times_and_place_in_the_order() {
+        let playlist = PlaylistEffect::new(
+            None,
+            vec![scene(1, 100), scene(2, 300), scene(3, 500)],
+            PlaylistTransition::Cut,
+            None,
+        );
+        let mut stack: Vec<Box<dyn Effect>> = vec![Box::new(playlist)];
+
+        let mut clock = FrameClock::new(Instant::from_millis(0));
+        stack.update(&clock);
+        assert!(stack.move_effect(EffectId(1), 2));
+        assert_eq!(showing(&stack), Some(EffectId(1)));
+
+        // Each scene still shows for its own dwell time, in the order they were first given.
+        for (ms, id) in [(99, 1), (100, 2), (399, 2), (400, 3), (899, 3), (900, 1)] {
+            clock.tick(Instant::from_millis(ms));
+            stack.update(&clock);
+            assert_eq!(showing(&stack), Some(EffectId(id)), "{ms}ms");
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::effect::CHUNK_SIZE;
+use crate::render::Rgba;
+
+/// A scratch layer big enough for one chunk of pixels.
+pub type Layer = Box<[Rgba; CHUNK_SIZE]>;
+
+/// Scratch layers lent to effects while they draw.
+///
+/// An effect that needs somewhere to draw its children takes a layer before drawing them and
+/// gives it back once it's done with it, so the layers in use at any moment are the ones held
+/// by the effects currently drawing. The pool only grows when every layer it has is in use, so
+/// its size is bounded by how deeply effects are nested rather than by how many effects there
+/// are, and it stops allocating once it has seen the deepest frame.
+pub struct BufferPool {
+    free: Vec<Layer>,
+    allocated: usize,
+}
+
+impl BufferPool {
+    pub const fn new() -> Self {
+        Self {
+            free: Vec::new(),
+            allocated: 0,
+        }
+    }
+
+    /// Borrows a layer, allocating a new one only if every layer is already in use. Its
+    /// contents are whatever was last drawn into it.
+    pub fn take(&mut self) -> Layer {
+        self.free.pop().unwrap_or_else(|| {
+            self.allocated += 1;
+            Box::new([Rgba::TRANSPARENT; CHUNK_SIZE])
+        })
+    }
+
+    /// Gives back a layer taken from this pool.
+    pub fn give(&mut self, layer: Layer) {
+        self.free.push(layer);
+    }
+
+    /// The number of layers the pool has allocated, which is the deepest that effects have been
+    /// nested so far.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,93 @@
+use alloc::boxed::Box;
+use embassy_time::{Duration, Instant};
+
+use crate::effect::{
+    BlendMode, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock, Wrapped,
+};
+use crate::render::{Hsv, Rgb, Rgba, LED_COUNT};
+
+/// Cycles the hue of the strip around the color wheel.
+///
+/// With a wrapped effect, the rainbow takes the wrapped effect's place, showing only where it's
+/// opaque. Without one, the rainbow fills the strip.
+pub struct RainbowCycleEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// How far through the current period the effect is, from `0.0` to `1.0`.
+    phase: f32,
+    period: Duration,
+    /// How many times the hue wheel repeats across the length of the strip.
+    spread: f32,
+    saturation: f32,
+    value: f32,
+    wrapped: Wrapped,
+}
+
+impl RainbowCycleEffect {
+    pub fn new(
+        id: Option<EffectId>,
+        period: Duration,
+        spread: f32,
+        saturation: f32,
+        value: f32,
+        wrapped: Option<Box<dyn Effect>>,
+    ) -> Self {
+        Self {
+            id,
+            start: None,
+            phase: 0.0,
+            period,
+            spread,
+            saturation,
+            value,
+            wrapped: Wrapped::new(wrapped),
+        }
+    }
+}
+
+impl Effect for RainbowCycleEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        match self.wrapped.children().first() {
+            Some(effect) => effect.display_mode(),
+            None => DisplayMode::Opaque,
+        }
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.wrapped.blend_mode()
+    }
+
+    fn children(&self) -> &[Box<dyn Effect>] {
+        self.wrapped.children()
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        self.wrapped.children_mut()
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        if let Some(event) = self.wrapped.update(clock) {
+            return Some(event);
+        }
+
+        let start = *self.start.get_or_insert(clock.now);
+        self.phase = clock.phase(start, self.period);
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        let t = self.phase;
+        let (spread, saturation, value) = (self.spread, self.saturation, self.value);
+
+        self.wrapped.apply(offset, buffer, pool, |i, wrapped| {
+            let x = (offset + i) as f32 / LED_COUNT as f32;
+            let color: Rgb = Hsv::new(t + x * spread, saturation, value).into();
+            Rgba::from_rgb(color, wrapped.a)
+        });
+    }
+}
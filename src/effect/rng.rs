@@ -0,0 +1,84 @@
+/// A small, seedable pseudo-random number generator for effects.
+///
+/// Every randomized effect owns its own `Rng` so that its output depends only on its seed and the
+/// times it's updated with, never on what other effects are doing. Two effects created with the
+/// same seed and updated with the same frame clocks render identical frames.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        // Scramble the seed so that similar seeds don't produce similar sequences, and so that
+        // the state is never zero, which xorshift can't escape.
+        let mut z = seed.wrapping_add(0x9e37_79b9);
+        z = (z ^ (z >> 16)).wrapping_mul(0x85eb_ca6b);
+        z = (z ^ (z >> 13)).wrapping_mul(0xc2b2_ae35);
+        z ^= z >> 16;
+
+        Self {
+            state: if z == 0 { 0x9e37_79b9 } else { z },
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        // xorshift32
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Returns a value in the range `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        // Use the top 24 bits, which is all the precision an f32 mantissa has.
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Returns a value in the range `0..bound`.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        ((self.next_u32() as u64 * bound as u64) >> 32) as u32
+    }
+
+    /// Shuffles `items` in place, with every order equally likely.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        // Fisher-Yates shuffle.
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn shuffle_produces_a_permutation() {
+        for seed in 0..16 {
+            let mut items: Vec<u32> = (0..100).collect();
+            Rng::new(seed).shuffle(&mut items);
+
+            let mut sorted = items.clone();
+            sorted.sort_unstable();
+            assert!(sorted.iter().copied().eq(0..100));
+            assert_ne!(items, sorted);
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+}
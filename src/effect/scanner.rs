@@ -0,0 +1,74 @@
+use embassy_time::{Duration, Instant};
+
+use crate::effect::{
+    BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FadeCurve, FrameClock,
+};
+use crate::render::{Rgb, Rgba, LED_COUNT};
+
+/// An eye that sweeps back and forth along the strip.
+///
+/// Each sweep follows `curve`, so [`FadeCurve::Smoothstep`] slows the eye down as it turns around
+/// at the ends, and [`FadeCurve::Linear`] bounces it off of them.
+pub struct ScannerEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// How far through the current period the effect is, from `0.0` to `1.0`.
+    phase: f32,
+    /// The time for one sweep there and back.
+    period: Duration,
+    /// The width of the eye, in pixels.
+    width: f32,
+    color: Rgb,
+    curve: FadeCurve,
+}
+
+impl ScannerEffect {
+    pub fn new(
+        id: Option<EffectId>,
+        period: Duration,
+        width: f32,
+        color: Rgb,
+        curve: FadeCurve,
+    ) -> Self {
+        Self {
+            id,
+            start: None,
+            phase: 0.0,
+            period,
+            width,
+            color,
+            curve,
+        }
+    }
+}
+
+impl Effect for ScannerEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let start = *self.start.get_or_insert(clock.now);
+        self.phase = clock.phase(start, self.period);
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], _pool: &mut BufferPool) {
+        let t = self.phase;
+        let sweep = if t < 0.5 { 2.0 * t } else { 2.0 - 2.0 * t };
+        let eye = self.curve.apply(sweep.clamp(0.0, 1.0)) * (LED_COUNT - 1) as f32;
+
+        let radius = (self.width / 2.0).max(0.5);
+
+        for (pixel, i) in buffer.iter_mut().zip(offset..) {
+            let a = 1.0 - (i as f32 - eye).abs() / radius;
+
+            *pixel = Rgba::from_rgb(self.color, a.clamp(0.0, 1.0));
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::mem;
+
+use embassy_time::Duration;
+
+use crate::effect::{empty, Effect, EffectId, FadeCurve, FadeDirection, FadeTransitionEffect};
+
+/// Manages individual effects in a stack by their [`EffectId`].
+///
+/// Every lookup searches the whole tree, including effects nested inside of wrappers and inner
+/// stacks, and acts on the first effect found with a matching ID.
+pub trait EffectStack {
+    fn find_effect(&self, id: EffectId) -> Option<&dyn Effect>;
+    fn find_effect_mut(&mut self, id: EffectId) -> Option<&mut Box<dyn Effect>>;
+
+    /// Puts `effect` in the place of the effect with `id`, and returns the old one.
+    fn replace_effect(&mut self, id: EffectId, effect: Box<dyn Effect>) -> Option<Box<dyn Effect>>;
+
+    /// Takes the effect with `id` out of the tree.
+    fn remove_effect(&mut self, id: EffectId) -> Option<Box<dyn Effect>>;
+
+    /// Fades out the effect with `id`, removing it once the fade completes. Returns whether the
+    /// effect was found.
+    fn fade_out_effect(&mut self, id: EffectId, duration: Duration) -> bool;
+
+    /// Moves the effect with `id` to `index` among its siblings, with `0` being the bottom.
+    /// Returns whether the effect was moved, which it isn't if it wasn't found or its parent keeps
+    /// its children in an order of its own.
+    fn move_effect(&mut self, id: EffectId, index: usize) -> bool;
+}
+
+impl EffectStack for Vec<Box<dyn Effect>> {
+    fn find_effect(&self, id: EffectId) -> Option<&dyn Effect> {
+        let (parent, index) = find_parent(self, id)?;
+        Some(parent.children()[index].as_ref())
+    }
+
+    fn find_effect_mut(&mut self, id: EffectId) -> Option<&mut Box<dyn Effect>> {
+        let (parent, index) = find_parent_mut(self, id)?;
+        Some(&mut parent.children_mut()[index])
+    }
+
+    fn replace_effect(&mut self, id: EffectId, effect: Box<dyn Effect>) -> Option<Box<dyn Effect>> {
+        let slot = self.find_effect_mut(id)?;
+        Some(mem::replace(slot, effect))
+    }
+
+    fn remove_effect(&mut self, id: EffectId) -> Option<Box<dyn Effect>> {
+        let (parent, index) = find_parent_mut(self, id)?;
+        parent.remove_child(index)
+    }
+
+    fn fade_out_effect(&mut self, id: EffectId, duration: Duration) -> bool {
+        let Some(slot) = self.find_effect_mut(id) else {
+            return false;
+        };
+
+        let effect = mem::replace(slot, empty());
+        *slot = Box::new(FadeTransitionEffect::new(
+            None,
+            duration,
+            FadeCurve::Linear,
+            FadeDirection::Out,
+            Some(effect),
+        ));
+
+        true
+    }
+
+    fn move_effect(&mut self, id: EffectId, index: usize) -> bool {
+        let Some((parent, from)) = find_parent_mut(self, id) else {
+            return false;
+        };
+
+        let to = index.min(parent.children().len() - 1);
+        parent.move_child(from, to)
+    }
+}
+
+/// Finds the effect that directly contains the effect with `id`, and the index of that effect
+/// among its children.
+///
+/// The tree is searched depth first, so a child's descendants are searched before its later
+/// siblings. Every lookup goes through here so that they all settle on the same effect.
+fn find_parent(parent: &dyn Effect, id: EffectId) -> Option<(&dyn Effect, usize)> {
+    for (index, child) in parent.children().iter().enumerate() {
+        if child.id() == Some(id) {
+            return Some((parent, index));
+        }
+
+        if let Some(found) = find_parent(child.as_ref(), id) {
+            return Some(found);
+        }
+    }
+
+    None
+}
+
+/// Like [`find_parent`], but for changing what's found.
+fn find_parent_mut(parent: &mut dyn Effect, id: EffectId) -> Option<(&mut dyn Effect, usize)> {
+    // Find the branch that holds the effect first, then descend into just that branch.
+    let index = parent
+        .children()
+        .iter()
+        .position(|child| child.id() == Some(id) || find_parent(child.as_ref(), id).is_some())?;
+
+    if parent.children()[index].id() == Some(id) {
+        return Some((parent, index));
+    }
+
+    find_parent_mut(parent.children_mut()[index].as_mut(), id)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use embassy_time::Instant;
+
+    use super::*;
+    use crate::effect::{BlendMode, FrameClock, Keyframe, LayerEffect, Playback, TimelineEffect};
+    use crate::render::Rgb;
+
+    fn layer(id: u32, wrapped: Box<dyn Effect>) -> Box<dyn Effect> {
+        Box::new(LayerEffect::new(
+            Some(EffectId(id)),
+            BlendMode::Normal,
+            1.0,
+            wrapped,
+        ))
+    }
+
+    fn color(r: f32) -> Box<dyn Effect> {
+        Box::new(Rgb::new(r, 0.0, 0.0))
+    }
+
+    fn ids(effects: &[Box<dyn Effect>]) -> Vec<Option<EffectId>> {
+        effects.iter().map(|effect| effect.id()).collect()
+    }
+
+    /// The color wrapped by the layer with `id`.
+    fn wrapped_color(stack: &Vec<Box<dyn Effect>>, id: u32) -> Option<Rgb> {
+        let effect = stack.find_effect(EffectId(id))?;
+        effect.children()[0].downcast_ref::<Rgb>().copied()
+    }
+
+    #[test]
+    fn finds_effects_nested_in_wrappers_and_inner_stacks() {
+        let inner: Vec<Box<dyn Effect>> = vec![layer(2, layer(3, color(0.3)))];
+        let mut stack: Vec<Box<dyn Effect>> = vec![layer(1, color(0.1)), Box::new(inner)];
+
+        assert_eq!(wrapped_color(&stack, 3), Some(Rgb::new(0.3, 0.0, 0.0)));
+        assert!(stack.find_effect_mut(EffectId(3)).is_some());
+        assert!(stack.find_effect(EffectId(4)).is_none());
+        assert!(stack.find_effect_mut(EffectId(4)).is_none());
+    }
+
+    #[test]
+    fn every_lookup_acts_on_the_first_match_depth_first() {
+        // The nested effect with ID 7 comes before its parent's later sibling with the same ID.
+        let mut stack: Vec<Box<dyn Effect>> =
+            vec![layer(1, layer(7, color(0.1))), layer(7, color(0.2))];
+
+        assert_eq!(wrapped_color(&stack, 7), Some(Rgb::new(0.1, 0.0, 0.0)));
+
+        let old = stack
+            .replace_effect(EffectId(7), layer(7, color(0.3)))
+            .unwrap();
+        assert_eq!(
+            old.children()[0].downcast_ref::<Rgb>(),
+            Some(&Rgb::new(0.1, 0.0, 0.0))
+        );
+        assert_eq!(wrapped_color(&stack, 7), Some(Rgb::new(0.3, 0.0, 0.0)));
+
+        assert!(stack.remove_effect(EffectId(7)).is_some());
+        assert_eq!(wrapped_color(&stack, 7), Some(Rgb::new(0.2, 0.0, 0.0)));
+        assert_eq!(ids(&stack), [Some(EffectId(1)), Some(EffectId(7))]);
+
+        assert!(stack.remove_effect(EffectId(7)).is_some());
+        assert!(stack.remove_effect(EffectId(7)).is_none());
+        assert_eq!(ids(&stack), [Some(EffectId(1))]);
+    }
+
+    #[test]
+    fn moves_effects_within_their_siblings() {
+        let mut stack: Vec<Box<dyn Effect>> = vec![
+            layer(1, color(0.1)),
+            layer(2, color(0.2)),
+            layer(3, color(0.3)),
+        ];
+
+        // Indices past the top clamp to the top.
+        assert!(stack.move_effect(EffectId(1), 10));
+        assert_eq!(
+            ids(&stack),
+            [Some(EffectId(2)), Some(EffectId(3)), Some(EffectId(1))]
+        );
+
+        assert!(stack.move_effect(EffectId(1), 0));
+        assert_eq!(
+            ids(&stack),
+            [Some(EffectId(1)), Some(EffectId(2)), Some(EffectId(3))]
+        );
+
+        assert!(stack.move_effect(EffectId(2), 1));
+        assert_eq!(
+            ids(&stack),
+            [Some(EffectId(1)), Some(EffectId(2)), Some(EffectId(3))]
+        );
+
+        assert!(!stack.move_effect(EffectId(4), 0));
+    }
+
+    #[test]
+    fn keyframe_effects_stay_in_place() {
+        let keyframes = vec![
+            Keyframe::new(
+                Duration::from_millis(0),
+                layer(1, color(0.1)),
+                FadeCurve::Linear,
+            ),
+            Keyframe::new(
+                Duration::from_millis(100),
+                layer(2, color(0.2)),
+                FadeCurve::Linear,
+            ),
+        ];
+        let timeline = TimelineEffect::new(None, Playback::Loop, keyframes);
+        let mut stack: Vec<Box<dyn Effect>> = vec![Box::new(timeline)];
+
+        assert!(!stack.move_effect(EffectId(1), 1));
+        assert_eq!(
+            ids(stack[0].children()),
+            [Some(EffectId(1)), Some(EffectId(2))]
+        );
+    }
+
+    #[test]
+    fn fades_out_and_removes_effects() {
+        let mut stack: Vec<Box<dyn Effect>> = vec![layer(1, color(0.1)), layer(2, color(0.2))];
+
+        assert!(stack.fade_out_effect(EffectId(2), Duration::from_millis(100)));
+        assert!(!stack.fade_out_effect(EffectId(3), Duration::from_millis(100)));
+
+        // The effect is still found while it fades, inside of the fade.
+        assert!(stack[1].downcast_ref::<FadeTransitionEffect>().is_some());
+        assert!(stack.find_effect(EffectId(2)).is_some());
+
+        let mut clock = FrameClock::new(Instant::from_millis(0));
+        stack.update(&clock);
+        assert_eq!(stack.len(), 2);
+
+        clock.tick(Instant::from_millis(100));
+        stack.update(&clock);
+        assert_eq!(ids(&stack), [Some(EffectId(1))]);
+        assert!(stack.find_effect(EffectId(2)).is_none());
+    }
+}
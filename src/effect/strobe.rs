@@ -0,0 +1,91 @@
+use embassy_time::{Duration, Instant};
+use log::warn;
+
+use crate::effect::{BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock};
+use crate::render::{Rgb, Rgba};
+
+/// The fastest a strobe is allowed to flash, in flashes per second.
+///
+/// Flashing more than three times a second is the commonly cited threshold for triggering
+/// photosensitive seizures, so no configuration is allowed to exceed it.
+pub const MAX_STROBE_FREQUENCY: f32 = 3.0;
+
+/// The largest change in luminance that a flash is allowed to make to any pixel.
+pub const MAX_STROBE_CONTRAST: f32 = 0.8;
+
+pub struct StrobeEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// How far through the current period the effect is, from `0.0` to `1.0`.
+    phase: f32,
+    frequency: f32,
+    period: Duration,
+    /// The fraction of each period that the flash is on.
+    duty_cycle: f32,
+    /// The flash color, with its alpha limited to respect [`MAX_STROBE_CONTRAST`].
+    flash: Rgba,
+}
+
+impl StrobeEffect {
+    /// Creates a strobe flashing `frequency` times per second.
+    ///
+    /// Frequencies above [`MAX_STROBE_FREQUENCY`] are clamped to it.
+    pub fn new(id: Option<EffectId>, frequency: f32, duty_cycle: f32, color: Rgb) -> Self {
+        let frequency = if frequency > MAX_STROBE_FREQUENCY {
+            warn!("Strobe frequency of {frequency} Hz clamped to {MAX_STROBE_FREQUENCY} Hz.");
+            MAX_STROBE_FREQUENCY
+        } else {
+            frequency
+        };
+
+        // The flash is drawn on its own layer without knowing what's beneath it, so assume the
+        // worst: that the pixels beneath are as far from the flash's luminance as possible.
+        let luminance = color.luminance();
+        let contrast = luminance.max(1.0 - luminance);
+        let a = if contrast > MAX_STROBE_CONTRAST {
+            MAX_STROBE_CONTRAST / contrast
+        } else {
+            1.0
+        };
+
+        Self {
+            id,
+            start: None,
+            phase: 0.0,
+            frequency,
+            period: Duration::from_micros((1_000_000.0 / frequency) as u64),
+            duty_cycle: duty_cycle.clamp(0.0, 1.0),
+            flash: Rgba::from_rgb(color, a),
+        }
+    }
+}
+
+impl Effect for StrobeEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn flash_frequency(&self) -> Option<f32> {
+        Some(self.frequency)
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let start = *self.start.get_or_insert(clock.now);
+        self.phase = clock.phase(start, self.period);
+
+        None
+    }
+
+    fn apply(&mut self, _offset: usize, buffer: &mut [Rgba], _pool: &mut BufferPool) {
+        let t = self.phase;
+        if t < self.duty_cycle {
+            buffer.fill(self.flash);
+        } else {
+            buffer.fill(Rgba::TRANSPARENT);
+        }
+    }
+}
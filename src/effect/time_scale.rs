@@ -0,0 +1,185 @@
+use alloc::boxed::Box;
+use core::slice;
+use embassy_time::{Duration, Instant};
+use log::warn;
+
+use crate::effect::{
+    BlendMode, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock,
+    MAX_STROBE_FREQUENCY,
+};
+use crate::render::Rgba;
+
+/// Plays the wrapped effect on its own timeline, which can be sped up, slowed down, paused,
+/// reversed, or frozen on a single frame.
+///
+/// The wrapped effect sees a clock that advances by the real time between frames multiplied by
+/// the speed. While the timeline runs backwards, the wrapped effect sees no time elapse between
+/// frames, so effects that simulate from one frame to the next hold still rather than rewind.
+///
+/// Speeding up an effect speeds up its flashes too, so the speed is clamped to keep anything
+/// that flashes under [`MAX_STROBE_FREQUENCY`] in real time.
+pub struct TimeScaleEffect {
+    id: Option<EffectId>,
+    /// The clock that the wrapped effect sees, once the effect has started.
+    clock: Option<FrameClock>,
+    /// Where the wrapped effect's timeline began.
+    origin: Instant,
+    /// Ticks of scaled time too small to have moved the clock yet.
+    remainder: f32,
+    speed: f32,
+    paused: bool,
+    /// A point on the timeline, relative to `origin`, to jump to on the next update.
+    seek: Option<Duration>,
+    wrapped: Box<dyn Effect>,
+}
+
+impl TimeScaleEffect {
+    /// Creates a timeline that plays `speed` times faster than real time. Negative speeds play
+    /// in reverse.
+    pub fn new(id: Option<EffectId>, speed: f32, wrapped: Box<dyn Effect>) -> Self {
+        let mut effect = Self {
+            id,
+            clock: None,
+            origin: Instant::from_ticks(0),
+            remainder: 0.0,
+            speed,
+            paused: false,
+            seek: None,
+            wrapped,
+        };
+        effect.limit_speed();
+        effect
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Negative speeds play in reverse.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+        self.limit_speed();
+    }
+
+    /// Flips the direction that the timeline plays in.
+    pub fn reverse(&mut self) {
+        self.speed = -self.speed;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Jumps to the frame `position` into the wrapped effect's timeline and pauses there.
+    pub fn freeze_at(&mut self, position: Duration) {
+        self.seek = Some(position);
+        self.paused = true;
+    }
+
+    /// Slows the timeline down if it would play anything that flashes too quickly.
+    fn limit_speed(&mut self) {
+        let Some(frequency) = self.wrapped.flash_frequency() else {
+            return;
+        };
+
+        let max_speed = MAX_STROBE_FREQUENCY / frequency;
+        if self.speed.abs() > max_speed {
+            warn!("Time scale speed of {} clamped to {max_speed}.", self.speed);
+            self.speed = if self.speed < 0.0 {
+                -max_speed
+            } else {
+                max_speed
+            };
+        }
+    }
+}
+
+/// Moves `now` by `ticks` along the timeline, which may be negative.
+fn advance(now: Instant, ticks: i64) -> Instant {
+    if ticks >= 0 {
+        now + Duration::from_ticks(ticks as u64)
+    } else {
+        now.checked_sub(Duration::from_ticks(ticks.unsigned_abs()))
+            .unwrap_or(Instant::from_ticks(0))
+    }
+}
+
+impl Effect for TimeScaleEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        self.wrapped.display_mode()
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.wrapped.blend_mode()
+    }
+
+    fn children(&self) -> &[Box<dyn Effect>] {
+        slice::from_ref(&self.wrapped)
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        slice::from_mut(&mut self.wrapped)
+    }
+
+    fn flash_frequency(&self) -> Option<f32> {
+        self.wrapped
+            .flash_frequency()
+            .map(|frequency| frequency * self.speed.abs())
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        // The wrapped effect may have changed since the last update.
+        self.limit_speed();
+
+        let scaled = match self.clock {
+            // The wrapped effect's timeline starts in step with the real one.
+            None => {
+                self.origin = clock.now;
+                self.clock.insert(FrameClock::new(clock.now))
+            }
+            Some(ref mut scaled) => {
+                let mut now = scaled.now;
+
+                if let Some(position) = self.seek.take() {
+                    now = self.origin + position;
+                    self.remainder = 0.0;
+                }
+
+                if !self.paused {
+                    let ticks = clock.elapsed.as_ticks() as f32 * self.speed + self.remainder;
+                    let whole = ticks as i64;
+                    self.remainder = ticks - whole as f32;
+                    now = advance(now, whole);
+                }
+
+                scaled.tick(now);
+                scaled
+            }
+        };
+
+        match self.wrapped.update(scaled) {
+            Some(EffectEvent::Replace(new_effect)) => {
+                self.wrapped = new_effect;
+                None
+            }
+            Some(EffectEvent::Remove) => Some(EffectEvent::Remove),
+            None => None,
+        }
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        self.wrapped.apply(offset, buffer, pool);
+    }
+}
@@ -0,0 +1,187 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use embassy_time::{Duration, Instant};
+
+use crate::effect::{
+    empty, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FadeCurve, FrameClock,
+};
+use crate::render::{Rgb, Rgba};
+
+/// What a [`TimelineEffect`] does once it reaches its last keyframe.
+pub enum Playback {
+    /// Plays through once, then removes itself.
+    Once,
+    /// Jumps back to the first keyframe and plays again.
+    Loop,
+    /// Plays backwards to the first keyframe, then forwards again.
+    PingPong,
+}
+
+/// A point on a [`TimelineEffect`]'s timeline.
+pub struct Keyframe {
+    /// The time, from the start of the timeline, at which `effect` is fully shown.
+    pub time: Duration,
+    pub effect: Box<dyn Effect>,
+    /// Eases the transition from this keyframe into the next one.
+    pub curve: FadeCurve,
+}
+
+impl Keyframe {
+    pub fn new(time: Duration, effect: Box<dyn Effect>, curve: FadeCurve) -> Self {
+        Self {
+            time,
+            effect,
+            curve,
+        }
+    }
+
+    /// A keyframe that shows a single color.
+    pub fn color(time: Duration, color: Rgb, curve: FadeCurve) -> Self {
+        Self::new(time, Box::new(color), curve)
+    }
+}
+
+/// Crossfades between keyframes at exact times.
+///
+/// Before the first keyframe, the first keyframe's effect is shown.
+pub struct TimelineEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// The keyframe being transitioned from.
+    from: usize,
+    /// How far the transition into the next keyframe is, after easing, from `0.0` to `1.0`.
+    mix: f32,
+    playback: Playback,
+    times: Vec<Duration>,
+    curves: Vec<FadeCurve>,
+    effects: Vec<Box<dyn Effect>>,
+}
+
+impl TimelineEffect {
+    /// Creates a timeline from `keyframes`, in any order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keyframes` is empty.
+    pub fn new(id: Option<EffectId>, playback: Playback, mut keyframes: Vec<Keyframe>) -> Self {
+        assert!(
+            !keyframes.is_empty(),
+            "a timeline needs at least one keyframe"
+        );
+        keyframes.sort_by_key(|keyframe| keyframe.time);
+
+        let mut times = Vec::with_capacity(keyframes.len());
+        let mut curves = Vec::with_capacity(keyframes.len());
+        let mut effects = Vec::with_capacity(keyframes.len());
+        for keyframe in keyframes {
+            times.push(keyframe.time);
+            curves.push(keyframe.curve);
+            effects.push(keyframe.effect);
+        }
+
+        Self {
+            id,
+            start: None,
+            from: 0,
+            mix: 0.0,
+            playback,
+            times,
+            curves,
+            effects,
+        }
+    }
+
+    /// The time of the last keyframe.
+    fn length(&self) -> Duration {
+        self.times[self.times.len() - 1]
+    }
+}
+
+impl Effect for TimelineEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn children(&self) -> &[Box<dyn Effect>] {
+        &self.effects
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        &mut self.effects
+    }
+
+    /// Keyframes are ordered by their times, so their effects stay where they are.
+    fn move_child(&mut self, _from: usize, _to: usize) -> bool {
+        false
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        // Keyframes that finish on their own leave a gap, so that the rest keep their times.
+        for effect in self.effects.iter_mut() {
+            match effect.update(clock) {
+                Some(EffectEvent::Replace(new_effect)) => *effect = new_effect,
+                Some(EffectEvent::Remove) => *effect = empty(),
+                None => (),
+            }
+        }
+
+        let start = *self.start.get_or_insert(clock.now);
+        let elapsed = clock.since(start).as_ticks();
+        let length = self.length().as_ticks();
+
+        let position = match self.playback {
+            Playback::Once if elapsed >= length => return Some(EffectEvent::Remove),
+            Playback::Once => elapsed,
+            Playback::Loop if length == 0 => 0,
+            Playback::Loop => elapsed % length,
+            Playback::PingPong if length == 0 => 0,
+            Playback::PingPong => {
+                let position = elapsed % (2 * length);
+                if position > length {
+                    2 * length - position
+                } else {
+                    position
+                }
+            }
+        };
+        let position = Duration::from_ticks(position);
+
+        let next = self.times.partition_point(|&time| time <= position);
+        if next == 0 || next == self.times.len() {
+            // Outside of the keyframes, so hold the nearest one.
+            self.from = next.saturating_sub(1);
+            self.mix = 0.0;
+        } else {
+            let from = next - 1;
+            let span = (self.times[next] - self.times[from]).as_ticks() as f32;
+            let t = (position - self.times[from]).as_ticks() as f32 / span;
+
+            self.from = from;
+            self.mix = self.curves[from].apply(t);
+        }
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        self.effects[self.from].apply(offset, buffer, pool);
+
+        if self.mix <= 0.0 {
+            return;
+        }
+
+        let mut layer = pool.take();
+        let next_layer = &mut layer[..buffer.len()];
+        self.effects[self.from + 1].apply(offset, next_layer, pool);
+
+        for (pixel, &next) in buffer.iter_mut().zip(next_layer.iter()) {
+            *pixel = pixel.lerp(next, self.mix);
+        }
+
+        pool.give(layer);
+    }
+}
@@ -0,0 +1,120 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::effect::{BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FrameClock, Rng};
+use crate::render::{Rgb, Rgba, LED_COUNT};
+
+pub struct TwinkleEffect {
+    id: Option<EffectId>,
+    rng: Rng,
+    color: Rgb,
+    /// The average number of pixels lit per second.
+    density: f32,
+    /// How much brightness a lit pixel loses per second.
+    fade_rate: f32,
+    brightness: Vec<f32>,
+}
+
+impl TwinkleEffect {
+    pub fn new(id: Option<EffectId>, seed: u32, color: Rgb, density: f32, fade_rate: f32) -> Self {
+        Self {
+            id,
+            rng: Rng::new(seed),
+            color,
+            density,
+            fade_rate,
+            brightness: vec![0.0; LED_COUNT],
+        }
+    }
+}
+
+impl Effect for TwinkleEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let dt = clock.elapsed.as_micros() as f32 / 1_000_000.0;
+
+        let fade = self.fade_rate * dt;
+        for brightness in self.brightness.iter_mut() {
+            *brightness = (*brightness - fade).max(0.0);
+        }
+
+        // Light the whole part of the expected count, and use the fractional part as the
+        // probability of lighting one more.
+        let expected = self.density * dt;
+        let mut count = expected as u32;
+        if self.rng.next_f32() < expected - count as f32 {
+            count += 1;
+        }
+
+        for _ in 0..count {
+            let i = self.rng.below(LED_COUNT as u32) as usize;
+            self.brightness[i] = 1.0;
+        }
+
+        None
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], _pool: &mut BufferPool) {
+        for (pixel, &brightness) in buffer.iter_mut().zip(&self.brightness[offset..]) {
+            *pixel = Rgba::from_rgb(self.color, brightness);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_time::Instant;
+
+    use super::*;
+    use crate::effect::CHUNK_SIZE;
+
+    fn render(effect: &mut TwinkleEffect, pool: &mut BufferPool) -> Vec<Rgba> {
+        let mut buffer = vec![Rgba::TRANSPARENT; LED_COUNT];
+        for (i, chunk) in buffer.chunks_mut(CHUNK_SIZE).enumerate() {
+            effect.apply(i * CHUNK_SIZE, chunk, pool);
+        }
+        buffer
+    }
+
+    #[test]
+    fn same_seed_renders_identical_frames() {
+        let mut a = TwinkleEffect::new(None, 7, Rgb::WHITE, 40.0, 2.0);
+        let mut b = TwinkleEffect::new(None, 7, Rgb::WHITE, 40.0, 2.0);
+        let mut pool = BufferPool::new();
+
+        // Uneven frame times, like the renderer sees.
+        let mut clock = FrameClock::new(Instant::from_millis(0));
+        for frame in 1..=120 {
+            clock.tick(Instant::from_millis(frame * 16 + frame % 3));
+            a.update(&clock);
+            b.update(&clock);
+
+            assert_eq!(render(&mut a, &mut pool), render(&mut b, &mut pool));
+        }
+
+        assert!(render(&mut a, &mut pool).iter().any(|pixel| pixel.a > 0.0));
+    }
+
+    #[test]
+    fn different_seeds_render_different_frames() {
+        let mut a = TwinkleEffect::new(None, 7, Rgb::WHITE, 40.0, 2.0);
+        let mut b = TwinkleEffect::new(None, 8, Rgb::WHITE, 40.0, 2.0);
+        let mut pool = BufferPool::new();
+
+        let mut clock = FrameClock::new(Instant::from_millis(0));
+        for frame in 1..=60 {
+            clock.tick(Instant::from_millis(frame * 16));
+            a.update(&clock);
+            b.update(&clock);
+        }
+
+        assert_ne!(render(&mut a, &mut pool), render(&mut b, &mut pool));
+    }
+}
@@ -0,0 +1,167 @@
+use alloc::boxed::Box;
+use embassy_time::{Duration, Instant};
+
+use crate::effect::{
+    BlendMode, BufferPool, DisplayMode, Effect, EffectEvent, EffectId, FadeCurve, FadeDirection,
+    FrameClock, Wrapped,
+};
+use crate::render::{Rgba, LED_COUNT};
+
+pub enum WipeOrigin {
+    /// The wipe starts at the first pixel.
+    Start,
+    /// The wipe starts at the last pixel.
+    End,
+    /// The wipe starts in the middle of the strip and spreads towards both ends.
+    Center,
+}
+
+impl WipeOrigin {
+    /// Returns how far along the wipe pixel `i` is, from `0.0` to `1.0`, along with the number of
+    /// pixels the wipe crosses.
+    fn position(&self, i: usize) -> (f32, f32) {
+        let x = i as f32 + 0.5;
+        let len = LED_COUNT as f32;
+
+        match self {
+            Self::Start => (x / len, len),
+            Self::End => (1.0 - x / len, len),
+            Self::Center => {
+                let half = len / 2.0;
+                ((x - half).abs() / half, half)
+            }
+        }
+    }
+}
+
+pub struct WipeTransitionEffect {
+    id: Option<EffectId>,
+    start: Option<Instant>,
+    /// How far through the transition the effect is, from `0.0` to `1.0`.
+    progress: f32,
+    duration: Duration,
+    fade_curve: FadeCurve,
+    fade_direction: FadeDirection,
+    origin: WipeOrigin,
+    wrapped: Wrapped,
+}
+
+impl WipeTransitionEffect {
+    pub fn new(
+        id: Option<EffectId>,
+        duration: Duration,
+        fade_curve: FadeCurve,
+        fade_direction: FadeDirection,
+        origin: WipeOrigin,
+        wrapped: Option<Box<dyn Effect>>,
+    ) -> Self {
+        Self {
+            id,
+            start: None,
+            progress: 0.0,
+            duration,
+            fade_curve,
+            fade_direction,
+            origin,
+            wrapped: Wrapped::new(wrapped),
+        }
+    }
+}
+
+impl Effect for WipeTransitionEffect {
+    fn id(&self) -> Option<EffectId> {
+        self.id
+    }
+
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode::Blend
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.wrapped.blend_mode()
+    }
+
+    fn children(&self) -> &[Box<dyn Effect>] {
+        self.wrapped.children()
+    }
+
+    fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        self.wrapped.children_mut()
+    }
+
+    fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let (progress, event) = self.wrapped.update_transition(
+            clock,
+            &mut self.start,
+            self.duration,
+            &self.fade_direction,
+        );
+        self.progress = progress;
+        event
+    }
+
+    fn apply(&mut self, offset: usize, buffer: &mut [Rgba], pool: &mut BufferPool) {
+        let mut t = self.progress;
+        t = self.fade_curve.apply(t);
+        t = self.fade_direction.apply(t);
+
+        let (origin, fade_direction) = (&self.origin, &self.fade_direction);
+        self.wrapped.apply(offset, buffer, pool, |i, wrapped| {
+            // Fading out mirrors where the wipe is as well as when, so that it hides the wrapped
+            // effect starting from the origin too.
+            let (position, span) = origin.position(offset + i);
+            let position = fade_direction.apply(position);
+
+            // Soften the edge of the wipe over about one pixel.
+            wrapped.scale_alpha((t - position) * span + 0.5)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::effect::CHUNK_SIZE;
+    use crate::render::Rgb;
+
+    fn alphas_halfway(fade_direction: FadeDirection) -> Vec<f32> {
+        let mut wipe = WipeTransitionEffect::new(
+            None,
+            Duration::from_millis(1000),
+            FadeCurve::Linear,
+            fade_direction,
+            WipeOrigin::Start,
+            Some(Box::new(Rgb::WHITE)),
+        );
+        let mut pool = BufferPool::new();
+
+        let mut clock = FrameClock::new(Instant::from_millis(0));
+        wipe.update(&clock);
+        clock.tick(Instant::from_millis(500));
+        wipe.update(&clock);
+
+        let mut buffer = vec![Rgba::TRANSPARENT; LED_COUNT];
+        for (i, chunk) in buffer.chunks_mut(CHUNK_SIZE).enumerate() {
+            wipe.apply(i * CHUNK_SIZE, chunk, &mut pool);
+        }
+        buffer.iter().map(|pixel| pixel.a).collect()
+    }
+
+    #[test]
+    fn wipes_in_and_out_from_the_origin() {
+        let half = LED_COUNT / 2;
+
+        // Wiping in shows the half nearest the origin first...
+        let alphas = alphas_halfway(FadeDirection::In);
+        assert!(alphas[..half - 1].iter().all(|&a| a >= 1.0));
+        assert!(alphas[half + 1..].iter().all(|&a| a <= 0.0));
+
+        // ...and wiping out hides it first.
+        let alphas = alphas_halfway(FadeDirection::Out);
+        assert!(alphas[..half - 1].iter().all(|&a| a <= 0.0));
+        assert!(alphas[half + 1..].iter().all(|&a| a >= 1.0));
+    }
+}
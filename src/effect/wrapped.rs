@@ -0,0 +1,113 @@
+use alloc::boxed::Box;
+use core::slice;
+use embassy_time::{Duration, Instant};
+
+use crate::effect::{BlendMode, BufferPool, Effect, EffectEvent, FadeDirection, FrameClock};
+use crate::render::Rgba;
+
+/// The optional child of an effect that changes how another effect is shown, such as a
+/// transition or a modulator. Without a child, black is shown in its place.
+pub(crate) struct Wrapped(Option<Box<dyn Effect>>);
+
+impl Wrapped {
+    pub(crate) fn new(effect: Option<Box<dyn Effect>>) -> Self {
+        Self(effect)
+    }
+
+    pub(crate) fn children(&self) -> &[Box<dyn Effect>] {
+        match &self.0 {
+            Some(effect) => slice::from_ref(effect),
+            None => &[],
+        }
+    }
+
+    pub(crate) fn children_mut(&mut self) -> &mut [Box<dyn Effect>] {
+        match &mut self.0 {
+            Some(effect) => slice::from_mut(effect),
+            None => &mut [],
+        }
+    }
+
+    /// The child's blend mode, so that wrapping an effect doesn't change how it's composited.
+    pub(crate) fn blend_mode(&self) -> BlendMode {
+        match &self.0 {
+            Some(effect) => effect.blend_mode(),
+            None => BlendMode::Normal,
+        }
+    }
+
+    /// Takes the child out, leaving black in its place.
+    pub(crate) fn take(&mut self) -> Option<Box<dyn Effect>> {
+        self.0.take()
+    }
+
+    /// Updates the child, replacing it if it asks to be. Returns [`EffectEvent::Remove`] if the
+    /// child removes itself, since there's nothing left for the wrapping effect to show.
+    pub(crate) fn update(&mut self, clock: &FrameClock) -> Option<EffectEvent> {
+        let effect = self.0.as_mut()?;
+
+        match effect.update(clock) {
+            Some(EffectEvent::Replace(new_effect)) => {
+                *effect = new_effect;
+                None
+            }
+            Some(EffectEvent::Remove) => {
+                self.0 = None;
+                Some(EffectEvent::Remove)
+            }
+            None => None,
+        }
+    }
+
+    /// Updates the child of a transition that started at `start`, or starts it now if it
+    /// hasn't yet. Returns how far through the transition it is, from `0.0` to `1.0`, along with
+    /// what should take its place once it's over: the child after fading in, or nothing after
+    /// fading out.
+    pub(crate) fn update_transition(
+        &mut self,
+        clock: &FrameClock,
+        start: &mut Option<Instant>,
+        duration: Duration,
+        direction: &FadeDirection,
+    ) -> (f32, Option<EffectEvent>) {
+        let event = self.update(clock);
+        let start = *start.get_or_insert(clock.now);
+        let progress = clock.progress(start, duration);
+
+        if event.is_some() || clock.since(start) < duration {
+            return (progress, event);
+        }
+
+        let event = match self.take() {
+            Some(effect) => match direction {
+                FadeDirection::In => EffectEvent::Replace(effect),
+                FadeDirection::Out => EffectEvent::Remove,
+            },
+            None => EffectEvent::Remove,
+        };
+        (progress, Some(event))
+    }
+
+    /// Draws the child into a layer, then fills `buffer` by passing each of the child's pixels
+    /// through `map`, along with its index in `buffer`.
+    pub(crate) fn apply(
+        &mut self,
+        offset: usize,
+        buffer: &mut [Rgba],
+        pool: &mut BufferPool,
+        mut map: impl FnMut(usize, Rgba) -> Rgba,
+    ) {
+        let mut layer = pool.take();
+        let wrapped_layer = &mut layer[..buffer.len()];
+        match self.0.as_mut() {
+            Some(effect) => effect.apply(offset, wrapped_layer, pool),
+            None => wrapped_layer.fill(Rgba::BLACK),
+        }
+
+        for (i, (pixel, &wrapped)) in buffer.iter_mut().zip(wrapped_layer.iter()).enumerate() {
+            *pixel = map(i, wrapped);
+        }
+
+        pool.give(layer);
+    }
+}
@@ -1,9 +1,14 @@
 #![no_std]
+// On the host, std's float methods shadow micromath's, which leaves its imports unused in tests.
+#![cfg_attr(test, allow(unused_imports))]
 
 extern crate alloc;
 
 pub mod effect;
+#[cfg(feature = "esp")]
 pub mod event;
+#[cfg(feature = "esp")]
 pub mod power;
 pub mod render;
+#[cfg(feature = "esp")]
 pub mod state;
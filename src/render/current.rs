@@ -0,0 +1,107 @@
+use crate::event::ChargerState;
+
+/// How much current a strip draws, used to estimate what a frame will draw before it's sent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CurrentModel {
+    /// The milliamps drawn by the red, green, blue and white channels of one pixel at full
+    /// brightness.
+    pub channel_ma: [f32; 4],
+    /// The milliamps drawn by each pixel even while it's dark.
+    pub idle_ma: f32,
+}
+
+impl CurrentModel {
+    /// Estimates the milliamps drawn by showing `levels`, which are 16 bit red, green, blue and
+    /// white channels.
+    pub fn estimate(&self, levels: &[[u16; 4]]) -> f32 {
+        let mut totals = [0u32; 4];
+        for pixel in levels {
+            for (total, &level) in totals.iter_mut().zip(pixel) {
+                *total += level as u32;
+            }
+        }
+
+        let lit: f32 = totals
+            .iter()
+            .zip(self.channel_ma)
+            .map(|(&total, ma)| total as f32 / u16::MAX as f32 * ma)
+            .sum();
+
+        self.idle_ma * levels.len() as f32 + lit
+    }
+}
+
+/// Keeps the strip under a current budget by dimming frames that would go over it.
+///
+/// The supply differs between running on the battery and running off the charger, so each has
+/// its own budget.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CurrentLimit {
+    pub model: CurrentModel,
+    /// The most milliamps the strip may draw while running on the battery.
+    pub battery_ma: f32,
+    /// The most milliamps the strip may draw while the charger is plugged in, which also has to
+    /// leave enough for charging.
+    pub charging_ma: f32,
+}
+
+impl CurrentLimit {
+    pub fn budget(&self, charger_state: ChargerState) -> f32 {
+        match charger_state {
+            ChargerState::PluggedIn => self.charging_ma,
+            ChargerState::Unplugged => self.battery_ma,
+        }
+    }
+
+    /// Scales `levels` down evenly, if needed, so that they draw no more than the budget.
+    pub fn apply(&self, levels: &mut [[u16; 4]], charger_state: ChargerState) -> CurrentDraw {
+        let requested_ma = self.model.estimate(levels);
+        let budget = self.budget(charger_state);
+        if requested_ma <= budget {
+            return CurrentDraw {
+                requested_ma,
+                estimated_ma: requested_ma,
+            };
+        }
+
+        // Dark pixels still draw their idle current, so only the rest can be scaled.
+        let idle_ma = self.model.idle_ma * levels.len() as f32;
+        let scale = ((budget - idle_ma) / (requested_ma - idle_ma)).clamp(0.0, 1.0);
+
+        // Scaled in fixed point, with 65536 meaning full strength.
+        let fixed_scale = (scale * 65536.0) as u32;
+        for pixel in levels.iter_mut() {
+            for level in pixel.iter_mut() {
+                *level = ((*level as u32 * fixed_scale) >> 16) as u16;
+            }
+        }
+
+        CurrentDraw {
+            requested_ma,
+            estimated_ma: idle_ma + (requested_ma - idle_ma) * scale,
+        }
+    }
+}
+
+impl Default for CurrentLimit {
+    /// Roughly a WS2812B or SK6812 strip, at about 20 milliamps per channel.
+    fn default() -> Self {
+        Self {
+            model: CurrentModel {
+                channel_ma: [20.0; 4],
+                idle_ma: 1.0,
+            },
+            battery_ma: 2000.0,
+            charging_ma: 500.0,
+        }
+    }
+}
+
+/// The estimated current drawn by the last frame, for diagnostics.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CurrentDraw {
+    /// What the frame would have drawn without the current limit, in milliamps.
+    pub requested_ma: f32,
+    /// What the frame was estimated to draw as it was sent, in milliamps.
+    pub estimated_ma: f32,
+}
@@ -0,0 +1,73 @@
+use micromath::F32Ext;
+
+use crate::render::Rgb;
+
+/// A color in hue, saturation, value form.
+///
+/// All components are in the range `0.0..=1.0`. The hue is measured in turns, so `0.0` and `1.0`
+/// are both red, and hues outside of that range wrap around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+}
+
+impl Hsv {
+    pub const fn new(h: f32, s: f32, v: f32) -> Self {
+        Self { h, s, v }
+    }
+}
+
+impl From<Hsv> for Rgb {
+    fn from(hsv: Hsv) -> Self {
+        let s = hsv.s.clamp(0.0, 1.0);
+        let v = hsv.v.clamp(0.0, 1.0);
+
+        let h = hsv.h.rem_euclid(1.0) * 6.0;
+
+        let sector = h.floor();
+        let f = h - sector;
+
+        let p = v * (1.0 - s);
+        let q = v * (1.0 - s * f);
+        let t = v * (1.0 - s * (1.0 - f));
+
+        match sector as u32 {
+            0 => Rgb::new(v, t, p),
+            1 => Rgb::new(q, v, p),
+            2 => Rgb::new(p, v, t),
+            3 => Rgb::new(p, q, v),
+            4 => Rgb::new(t, p, v),
+            _ => Rgb::new(v, p, q),
+        }
+    }
+}
+
+impl From<Rgb> for Hsv {
+    fn from(rgb: Rgb) -> Self {
+        let Rgb { r, g, b } = rgb.clamp();
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let sector = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+
+        Self {
+            h: sector / 6.0,
+            s,
+            v: max,
+        }
+    }
+}
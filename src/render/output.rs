@@ -0,0 +1,233 @@
+use micromath::F32Ext;
+
+use crate::render::{CurrentLimit, Rgb, LED_COUNT, ONE, ZERO};
+
+/// The number of entries in each channel of a [`GammaTable`].
+const GAMMA_TABLE_SIZE: usize = 1024;
+
+/// The gamma exponent for each channel of an output.
+///
+/// LEDs respond to their duty cycle roughly linearly, while eyes don't, so colors are raised to a
+/// power before they're sent out to look evenly spaced in brightness.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gamma {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Gamma {
+    /// The same gamma for every channel.
+    pub const fn uniform(gamma: f32) -> Self {
+        Self {
+            r: gamma,
+            g: gamma,
+            b: gamma,
+        }
+    }
+}
+
+/// The order that a strip expects the color channels of each pixel in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorOrder {
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+}
+
+impl ColorOrder {
+    /// Rearranges red, green and blue channels into this order.
+    pub fn arrange<T>(&self, [r, g, b]: [T; 3]) -> [T; 3] {
+        match self {
+            Self::Rgb => [r, g, b],
+            Self::Rbg => [r, b, g],
+            Self::Grb => [g, r, b],
+            Self::Gbr => [g, b, r],
+            Self::Brg => [b, r, g],
+            Self::Bgr => [b, g, r],
+        }
+    }
+}
+
+/// How a strip with a white LED in each pixel, such as the SK6812 RGBW, lights it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WhiteStrategy {
+    /// Moves the white that the color channels have in common onto the white LED, which keeps
+    /// colors accurate.
+    Replace,
+    /// Lights the white LED with the white that the color channels have in common, but leaves
+    /// the color channels as they are. Whites are brighter, but less accurate and more costly.
+    Boost,
+    /// Leaves the white LED off.
+    Off,
+}
+
+impl WhiteStrategy {
+    /// Splits color channels, in any order, into those channels and a white channel.
+    pub fn extract(&self, channels: [u16; 3]) -> [u16; 4] {
+        let white = channels.into_iter().min().unwrap_or(0);
+        let [a, b, c] = channels;
+
+        match self {
+            Self::Replace => [a - white, b - white, c - white, white],
+            Self::Boost => [a, b, c, white],
+            Self::Off => [a, b, c, 0],
+        }
+    }
+}
+
+/// How the renderer turns colors into what's sent to the LEDs. Changes take effect on the next
+/// frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OutputConfig {
+    /// Scales the whole frame, from `0.0` (off) to `1.0` (full).
+    pub brightness: f32,
+    /// Scales each channel to balance the white of the strip, with `1.0` leaving a channel as it
+    /// is.
+    pub color_correction: Rgb,
+    pub gamma: Gamma,
+    /// Spreads the precision lost in quantizing to 8 bits across frames, so that slow fades
+    /// and dim colors don't visibly step.
+    pub dithering: bool,
+    pub color_order: ColorOrder,
+    /// How to light the white LEDs of a strip that has them. They're always sent after the color
+    /// channels.
+    pub white: Option<WhiteStrategy>,
+    pub current_limit: CurrentLimit,
+}
+
+impl OutputConfig {
+    /// The brightness and color correction of each channel combined into one fixed point scale,
+    /// with `65536` meaning full strength.
+    ///
+    /// These are applied after gamma, so that they scale the light that's given off and leave
+    /// hues as they are.
+    pub fn channel_scales(&self) -> [u32; 3] {
+        let brightness = self.brightness.clamp(0.0, 1.0);
+        let correction = [
+            self.color_correction.r,
+            self.color_correction.g,
+            self.color_correction.b,
+        ];
+
+        correction.map(|c| (c.clamp(0.0, 1.0) * brightness * 65536.0 + 0.5) as u32)
+    }
+
+    /// The number of channels each pixel is sent with.
+    pub fn channels(&self) -> usize {
+        match self.white {
+            Some(_) => 4,
+            None => 3,
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            color_correction: Rgb::WHITE,
+            gamma: Gamma::uniform(2.0),
+            dithering: true,
+            color_order: ColorOrder::Grb,
+            white: None,
+            current_limit: CurrentLimit::default(),
+        }
+    }
+}
+
+/// Per-channel gamma curves, precomputed so that encoding a frame only has to look values up
+/// instead of raising every channel of every pixel to a power.
+pub struct GammaTable {
+    channels: [[u16; GAMMA_TABLE_SIZE]; 3],
+}
+
+impl GammaTable {
+    pub fn new(gamma: Gamma) -> Self {
+        let mut table = Self {
+            channels: [[0; GAMMA_TABLE_SIZE]; 3],
+        };
+        table.rebuild(gamma);
+        table
+    }
+
+    /// Recomputes the table in place for a new gamma.
+    pub fn rebuild(&mut self, gamma: Gamma) {
+        for (channel, gamma) in self.channels.iter_mut().zip([gamma.r, gamma.g, gamma.b]) {
+            // Zero is left as is, since the power of zero isn't reliable with approximate math.
+            for (i, entry) in channel.iter_mut().enumerate().skip(1) {
+                let x = i as f32 / (GAMMA_TABLE_SIZE - 1) as f32;
+                *entry = (x.powf(gamma).clamp(0.0, 1.0) * u16::MAX as f32 + 0.5) as u16;
+            }
+        }
+    }
+
+    /// Applies gamma to `color`, returning each channel scaled to the full range of a `u16`.
+    pub fn apply(&self, color: Rgb) -> [u16; 3] {
+        let index = |x: f32| (x.clamp(0.0, 1.0) * (GAMMA_TABLE_SIZE - 1) as f32 + 0.5) as usize;
+
+        [
+            self.channels[0][index(color.r)],
+            self.channels[1][index(color.g)],
+            self.channels[2][index(color.b)],
+        ]
+    }
+}
+
+/// Temporal dithering, which carries the error from quantizing each channel of each pixel over to
+/// the next frame.
+///
+/// A channel that falls between two 8 bit levels alternates between them, spending a share of
+/// frames at each that averages out to the level it should have been.
+pub struct Dither {
+    error: [[u8; 4]; LED_COUNT],
+}
+
+impl Dither {
+    pub const fn new() -> Self {
+        Self {
+            error: [[0; 4]; LED_COUNT],
+        }
+    }
+
+    /// Quantizes the 16 bit `channels` of the pixel at `index` to 8 bits.
+    pub fn quantize(&mut self, index: usize, channels: [u16; 4]) -> [u8; 4] {
+        let mut quantized = [0; 4];
+
+        for ((value, error), quantized) in channels
+            .into_iter()
+            .zip(self.error[index].iter_mut())
+            .zip(quantized.iter_mut())
+        {
+            let value = value as u32 + *error as u32;
+            let level = (value >> 8).min(u8::MAX as u32);
+
+            *quantized = level as u8;
+            *error = (value - (level << 8)).min(u8::MAX as u32) as u8;
+        }
+
+        quantized
+    }
+}
+
+impl Default for Dither {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes the pulses for one 8 bit channel, most significant bit first.
+pub(crate) fn write_channel(value: u8, pulses: &mut [u32]) {
+    let mut mask = 0x80;
+    for pulse in pulses.iter_mut().take(8) {
+        if value & mask != 0 {
+            *pulse = ONE;
+        } else {
+            *pulse = ZERO;
+        }
+        mask >>= 1;
+    }
+}
@@ -0,0 +1,55 @@
+use alloc::vec::Vec;
+
+use crate::render::Rgb;
+
+/// A set of colors positioned along `0.0..=1.0`, sampled by interpolating between neighbors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    stops: Vec<(f32, Rgb)>,
+}
+
+impl Palette {
+    /// Creates a palette from `(position, color)` stops, in any order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stops` is empty.
+    pub fn new(mut stops: Vec<(f32, Rgb)>) -> Self {
+        assert!(!stops.is_empty(), "a palette needs at least one stop");
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Creates a palette with `colors` spaced evenly from `0.0` to `1.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `colors` is empty.
+    pub fn even(colors: &[Rgb]) -> Self {
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        Self::new(
+            colors
+                .iter()
+                .enumerate()
+                .map(|(i, &color)| (i as f32 / last, color))
+                .collect(),
+        )
+    }
+
+    /// Returns the color at `t`. Positions outside of the first and last stops take the color of
+    /// the nearest stop.
+    pub fn sample(&self, t: f32) -> Rgb {
+        let next = self.stops.partition_point(|&(position, _)| position <= t);
+
+        if next == 0 {
+            return self.stops[0].1;
+        }
+        if next == self.stops.len() {
+            return self.stops[next - 1].1;
+        }
+
+        let (start, from) = self.stops[next - 1];
+        let (end, to) = self.stops[next];
+        from.lerp(to, (t - start) / (end - start))
+    }
+}
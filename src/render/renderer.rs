@@ -0,0 +1,255 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use embassy_futures::yield_now;
+use embassy_time::Instant;
+use esp_hal::gpio::Level;
+use esp_hal::rmt::{Rmt, TxChannelConfig, TxChannelCreatorAsync};
+use esp_hal::time::Rate;
+use log::info;
+#[cfg(feature = "alloc-stats")]
+use log::warn;
+
+use crate::effect::{BufferPool, Effect, FrameClock, CHUNK_SIZE};
+use crate::render::async_transmit::transmit;
+use crate::render::output::write_channel;
+use crate::render::{Dither, GammaTable, OutputConfig, Rgba, LED_COUNT, ZERO};
+use crate::state::State;
+
+/// How many pixels are translated into pulses between yields. Very non-scientific measurements
+/// suggest that this yields about once every 25 microseconds.
+const ENCODE_CHUNK_SIZE: usize = 4;
+
+#[embassy_executor::task]
+pub async fn renderer() {
+    let state = State::get().await;
+
+    // Effects write to the render buffer.
+    let mut render_buffer = [Rgba::TRANSPARENT; LED_COUNT];
+    // The render buffer is translated into 16 bit levels for each channel, which can be dimmed to
+    // stay under the current limit before they're quantized.
+    let mut levels = [[0; 4]; LED_COUNT];
+    // The levels are translated into pulse codes, which are sent to the remote control module.
+    // It has room for strips with four channels per pixel, but only what the strip needs is sent.
+    // At over 25KB, it's allocated once on the heap rather than kept in the task, which has to
+    // fit in the executor's task arena.
+    let mut pulse_buffer = vec![ZERO; LED_COUNT * 32 + 1].into_boxed_slice();
+
+    let mut rmt_channel = {
+        let mut peripherals = state.peripherals.lock().await;
+
+        let rmt_peripheral = peripherals.rmt.take().expect("rmt already taken");
+        let freq = Rate::from_mhz(80);
+        let rmt = Rmt::new(rmt_peripheral, freq)
+            .expect("could not initialize rmt")
+            .into_async();
+
+        let tx_config = TxChannelConfig::default()
+            .with_clk_divider(1)
+            .with_idle_output(true)
+            .with_idle_output_level(Level::Low)
+            .with_carrier_modulation(false);
+
+        let signal_pin = peripherals
+            .signal_1_pin
+            .take()
+            .expect("signal 1 pin already taken");
+
+        rmt.channel0
+            .configure(signal_pin, tx_config)
+            .expect("could not initialize signal 1")
+    };
+
+    let mut fps_acc = 0;
+    let mut fps_time = Instant::now();
+    let mut clock = FrameClock::new(Instant::now());
+    let mut pool = BufferPool::new();
+    let mut output_config = *state.output.lock().await;
+    let mut gamma_table = Box::new(GammaTable::new(output_config.gamma));
+    let mut dither = Dither::new();
+    #[cfg(feature = "alloc-stats")]
+    let mut allocating_frames = 0;
+    loop {
+        let frame_start = Instant::now();
+        clock.tick(frame_start);
+
+        // Bytes allocated by rendering this frame, counted only with the `alloc-stats` feature.
+        let mut allocated = 0;
+        #[cfg(feature = "alloc-stats")]
+        let layers_before = pool.allocated();
+
+        // Pick up any changes to the output settings.
+        let new_output_config = *state.output.lock().await;
+        if new_output_config != output_config {
+            if new_output_config.gamma != output_config.gamma {
+                gamma_table.rebuild(new_output_config.gamma);
+            }
+            output_config = new_output_config;
+        }
+
+        // Clear buffer.
+        render_buffer.fill(Rgba::TRANSPARENT);
+
+        // Update and render effects.
+        {
+            let mut effect_stack = state.effect_stack.lock().await;
+            count_allocations(&mut allocated, || effect_stack.update(&clock));
+            for (i, chunk) in render_buffer.chunks_mut(CHUNK_SIZE).enumerate() {
+                count_allocations(&mut allocated, || {
+                    effect_stack.apply(i * CHUNK_SIZE, chunk, &mut pool)
+                });
+                yield_now().await;
+            }
+        }
+        let t_a = frame_start.elapsed().as_micros();
+
+        // Translate the render buffer into pulses, dimmed to stay under the current limit.
+        for (pixels, levels) in render_buffer
+            .chunks(ENCODE_CHUNK_SIZE)
+            .zip(levels.chunks_mut(ENCODE_CHUNK_SIZE))
+        {
+            count_allocations(&mut allocated, || {
+                encode_levels(pixels, levels, &output_config, &gamma_table)
+            });
+            yield_now().await;
+        }
+
+        let charger_state = state.get_charger_state().await;
+        let draw = count_allocations(&mut allocated, || {
+            output_config
+                .current_limit
+                .apply(&mut levels, charger_state)
+        });
+        *state.current_draw.lock().await = draw;
+
+        let pulses_per_pixel = output_config.channels() * 8;
+        for (i, (levels, pulses)) in levels
+            .chunks(ENCODE_CHUNK_SIZE)
+            .zip(pulse_buffer.chunks_mut(ENCODE_CHUNK_SIZE * pulses_per_pixel))
+            .enumerate()
+        {
+            let dither = output_config.dithering.then_some(&mut dither);
+            count_allocations(&mut allocated, || {
+                write_pulses(
+                    i * ENCODE_CHUNK_SIZE,
+                    levels,
+                    pulses,
+                    &output_config,
+                    dither,
+                )
+            });
+            yield_now().await;
+        }
+
+        // A zero marks the end of the transmission.
+        let pulse_count = LED_COUNT * pulses_per_pixel;
+        pulse_buffer[pulse_count] = 0;
+        let t_b = frame_start.elapsed().as_micros() - t_a;
+
+        // Transmit the pulses on the RMT.
+        transmit(&mut rmt_channel, &pulse_buffer[..=pulse_count])
+            .await
+            .expect("could not transmit pulses");
+        let t_c = frame_start.elapsed().as_micros() - t_b - t_a;
+
+        #[cfg(feature = "alloc-stats")]
+        if allocated > 0 {
+            allocating_frames += 1;
+
+            // The pool grows until it has enough layers for the most that are drawn at once.
+            // Past that, rendering shouldn't allocate at all.
+            if pool.allocated() == layers_before {
+                warn!(
+                    "Rendering allocated {allocated} bytes in a frame that needed no new layers."
+                );
+            }
+        }
+
+        fps_acc += 1;
+        if fps_time.elapsed().as_millis() >= 1000 {
+            fps_time = Instant::now();
+            info!(
+                "FPS: {fps_acc}, effects({}, {} layers): {t_a}, pulses: {t_b}, transmit: {t_c}",
+                state.effect_stack.lock().await.len(),
+                pool.allocated(),
+            );
+            info!(
+                "Current: {}mA, {}mA requested",
+                draw.estimated_ma as u32, draw.requested_ma as u32,
+            );
+            fps_acc = 0;
+
+            #[cfg(feature = "alloc-stats")]
+            {
+                info!("Frames that allocated: {allocating_frames}");
+                allocating_frames = 0;
+            }
+        }
+    }
+}
+
+/// Applies gamma, brightness and color correction to each pixel, and splits off white for strips
+/// that have it, leaving 16 bit red, green, blue and white levels.
+fn encode_levels(
+    pixels: &[Rgba],
+    levels: &mut [[u16; 4]],
+    output_config: &OutputConfig,
+    gamma_table: &GammaTable,
+) {
+    let scales = output_config.channel_scales();
+
+    for (pixel, levels) in pixels.iter().zip(levels.iter_mut()) {
+        // Anything left transparent shows as black.
+        let channels = gamma_table.apply(pixel.flatten());
+        let channels = [0, 1, 2].map(|c| ((channels[c] as u32 * scales[c]) >> 16) as u16);
+
+        *levels = match output_config.white {
+            Some(white) => white.extract(channels),
+            None => [channels[0], channels[1], channels[2], 0],
+        };
+    }
+}
+
+/// Writes the pulses for `levels`, which start at pixel `offset` of the strip.
+fn write_pulses(
+    offset: usize,
+    levels: &[[u16; 4]],
+    pulses: &mut [u32],
+    output_config: &OutputConfig,
+    mut dither: Option<&mut Dither>,
+) {
+    let channel_count = output_config.channels();
+
+    for (i, (&pixel, pulses)) in levels
+        .iter()
+        .zip(pulses.chunks_mut(channel_count * 8))
+        .enumerate()
+    {
+        let [r, g, b, w] = match dither.as_mut() {
+            Some(dither) => dither.quantize(offset + i, pixel),
+            None => pixel.map(|channel| (channel >> 8) as u8),
+        };
+        let [c0, c1, c2] = output_config.color_order.arrange([r, g, b]);
+
+        for (&level, pulses) in [c0, c1, c2, w][..channel_count]
+            .iter()
+            .zip(pulses.chunks_mut(8))
+        {
+            write_channel(level, pulses);
+        }
+    }
+}
+
+/// Runs `f`, adding the bytes that it allocates on the heap to `allocated`. `f` can't yield, so
+/// nothing that other tasks allocate is counted.
+#[cfg(feature = "alloc-stats")]
+fn count_allocations<T>(allocated: &mut usize, f: impl FnOnce() -> T) -> T {
+    let before = esp_alloc::HEAP.stats().total_allocated;
+    let result = f();
+    *allocated += esp_alloc::HEAP.stats().total_allocated - before;
+    result
+}
+
+#[cfg(not(feature = "alloc-stats"))]
+fn count_allocations<T>(_allocated: &mut usize, f: impl FnOnce() -> T) -> T {
+    f()
+}
@@ -1,5 +1,3 @@
-use crate::render::{ONE, ZERO};
-
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Rgb {
     pub r: f32,
@@ -40,6 +38,11 @@ impl Rgb {
         }
     }
 
+    /// The relative luminance of the color, using the Rec. 709 channel weights.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
     pub fn quantize_u8(&self) -> (u8, u8, u8) {
         (
             (self.r * 255.0) as u8,
@@ -47,23 +50,4 @@ impl Rgb {
             (self.b * 255.0) as u8,
         )
     }
-
-    pub fn write_pulses(&self, pulses: &mut [u32]) {
-        fn write_u8(value: u8, buffer: &mut [u32]) {
-            let mut mask = 0x80;
-            for pulse in buffer.iter_mut().take(8) {
-                if value & mask != 0 {
-                    *pulse = ONE;
-                } else {
-                    *pulse = ZERO;
-                }
-                mask >>= 1;
-            }
-        }
-
-        let (r, g, b) = self.quantize_u8();
-        write_u8(g, &mut pulses[0..8]);
-        write_u8(r, &mut pulses[8..16]);
-        write_u8(b, &mut pulses[16..24]);
-    }
 }
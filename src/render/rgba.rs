@@ -0,0 +1,67 @@
+use crate::render::Rgb;
+
+/// A color with a straight (not premultiplied) alpha channel.
+///
+/// An alpha of `1.0` completely covers whatever is beneath the pixel, and an alpha of `0.0`
+/// leaves it showing through untouched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Rgba {
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn from_rgb(rgb: Rgb, a: f32) -> Self {
+        Self {
+            r: rgb.r,
+            g: rgb.g,
+            b: rgb.b,
+            a,
+        }
+    }
+
+    /// The color channels, ignoring alpha.
+    pub const fn rgb(&self) -> Rgb {
+        Rgb::new(self.r, self.g, self.b)
+    }
+
+    /// Returns the same color with its alpha multiplied by `opacity`.
+    pub fn scale_alpha(&self, opacity: f32) -> Self {
+        Self {
+            a: self.a * opacity.clamp(0.0, 1.0),
+            ..*self
+        }
+    }
+
+    /// Crossfades from this color to `other`. Each color is weighted by its alpha, so that the
+    /// color of a transparent pixel doesn't bleed into the result.
+    pub fn lerp(&self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let a = self.a * (1.0 - t) + other.a * t;
+        if a <= 0.0 {
+            return Self::TRANSPARENT;
+        }
+
+        Self::from_rgb(self.rgb().lerp(other.rgb(), other.a * t / a), a)
+    }
+
+    /// The color that this pixel shows when composited over black.
+    pub fn flatten(&self) -> Rgb {
+        Rgb::BLACK.lerp(self.rgb(), self.a)
+    }
+}
+
+impl From<Rgb> for Rgba {
+    fn from(rgb: Rgb) -> Self {
+        Self::from_rgb(rgb, 1.0)
+    }
+}
@@ -13,6 +13,7 @@ use esp_hal::timer::timg::TimerGroup;
 use crate::effect::Effect;
 use crate::event::{ButtonState, ChargerState, Event};
 use crate::power::Power;
+use crate::render::{CurrentDraw, OutputConfig, Rgb};
 
 static STATE: OnceLock<State> = OnceLock::new();
 
@@ -25,6 +26,9 @@ pub struct State {
     pub exit: Signal<()>,
     pub power: Mutex<Power>,
     pub effect_stack: Mutex<Vec<Box<dyn Effect>>>,
+    pub output: Mutex<OutputConfig>,
+    /// The estimated current drawn by the last frame that was sent.
+    pub current_draw: Mutex<CurrentDraw>,
 }
 
 pub type Channel<T, const N: usize> = EmbassyChannel<NoopRawMutex, T, N>;
@@ -58,6 +62,8 @@ impl State {
                 exit: Signal::new(),
                 power: Mutex::new(Power::new(hal.LPWR)),
                 effect_stack: Mutex::new(Vec::new()),
+                output: Mutex::new(OutputConfig::default()),
+                current_draw: Mutex::new(CurrentDraw::default()),
             })
             .expect("can't be set already");
 
@@ -83,6 +89,16 @@ impl State {
     pub async fn get_charger_state(&self) -> ChargerState {
         *self.charger_state.lock().await
     }
+
+    /// Sets the brightness of everything that's shown, from `0.0` (off) to `1.0` (full).
+    pub async fn set_brightness(&self, brightness: f32) {
+        self.output.lock().await.brightness = brightness;
+    }
+
+    /// Sets how much each channel is scaled to balance the white of the strip.
+    pub async fn set_color_correction(&self, color_correction: Rgb) {
+        self.output.lock().await.color_correction = color_correction;
+    }
 }
 
 impl fmt::Debug for State {
@@ -0,0 +1,139 @@
+//! Checks that rendering doesn't allocate once the buffer pool has grown to fit the effects.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use embassy_time::{Duration, Instant};
+use firmware::effect::{
+    BlendMode, BufferPool, CometBoundary, CometDirection, CometEffect, DissolveTransitionEffect,
+    Effect, FadeCurve, FadeDirection, FadeTransitionEffect, FrameClock, GradientEffect,
+    LayerEffect, ModulatorEffect, PlasmaEffect, TimeScaleEffect, TwinkleEffect, Waveform,
+    CHUNK_SIZE,
+};
+use firmware::render::{Palette, Rgb, Rgba, LED_COUNT};
+
+/// Counts the allocations made by the current thread while counting is on.
+struct CountingAllocator;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.get() {
+            ALLOCATIONS.set(ALLOCATIONS.get() + 1);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.get() {
+            ALLOCATIONS.set(ALLOCATIONS.get() + 1);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Counts the allocations made by `f` on this thread.
+fn count_allocations(f: impl FnOnce()) -> usize {
+    ALLOCATIONS.set(0);
+    COUNTING.set(true);
+    f();
+    COUNTING.set(false);
+    ALLOCATIONS.get()
+}
+
+/// Updates and draws `stack` the way the renderer does, one chunk at a time.
+fn render_frame(
+    stack: &mut Vec<Box<dyn Effect>>,
+    clock: &FrameClock,
+    buffer: &mut [Rgba],
+    pool: &mut BufferPool,
+) {
+    stack.update(clock);
+    for (i, chunk) in buffer.chunks_mut(CHUNK_SIZE).enumerate() {
+        stack.apply(i * CHUNK_SIZE, chunk, pool);
+    }
+}
+
+#[test]
+fn rendering_a_nested_stack_does_not_allocate() {
+    let palette = Palette::even(&[Rgb::new(1.0, 0.0, 0.0), Rgb::new(0.0, 0.0, 1.0)]);
+    let comet = CometEffect::new(
+        None,
+        Rgb::WHITE,
+        60.0,
+        20.0,
+        2.0,
+        CometDirection::Forward,
+        CometBoundary::Bounce,
+    );
+    let inner: Vec<Box<dyn Effect>> = vec![
+        Box::new(PlasmaEffect::new(None, 1, palette.clone(), 0.05, 0.5)),
+        Box::new(LayerEffect::new(None, BlendMode::Add, 0.8, Box::new(comet))),
+        Box::new(TwinkleEffect::new(None, 2, Rgb::WHITE, 20.0, 1.5)),
+    ];
+    let mut stack: Vec<Box<dyn Effect>> = vec![
+        Box::new(GradientEffect::new(
+            None,
+            palette,
+            Some(Duration::from_millis(4000)),
+        )),
+        Box::new(TimeScaleEffect::new(
+            None,
+            0.5,
+            Box::new(ModulatorEffect::new(
+                None,
+                Waveform::Sine,
+                Duration::from_millis(2000),
+                0.5,
+                0.5,
+                0.0,
+                Some(Box::new(FadeTransitionEffect::new(
+                    None,
+                    Duration::from_millis(60_000),
+                    FadeCurve::Smoothstep,
+                    FadeDirection::In,
+                    Some(Box::new(inner)),
+                ))),
+            )),
+        )),
+        Box::new(DissolveTransitionEffect::new(
+            None,
+            Duration::from_millis(60_000),
+            FadeCurve::Linear,
+            FadeDirection::In,
+            3,
+            Some(Box::new(Rgb::new(0.0, 0.2, 0.0))),
+        )),
+    ];
+
+    let mut buffer = vec![Rgba::TRANSPARENT; LED_COUNT];
+    let mut pool = BufferPool::new();
+    let mut clock = FrameClock::new(Instant::from_millis(0));
+
+    // The pool grows to fit the most layers drawn at once during the first frame.
+    for frame in 1..=10 {
+        clock.tick(Instant::from_millis(frame * 16));
+        render_frame(&mut stack, &clock, &mut buffer, &mut pool);
+    }
+    let layers = pool.allocated();
+
+    for frame in 11..=500 {
+        clock.tick(Instant::from_millis(frame * 16 + frame % 3));
+        let allocations =
+            count_allocations(|| render_frame(&mut stack, &clock, &mut buffer, &mut pool));
+        assert_eq!(allocations, 0, "frame {frame} allocated");
+    }
+
+    assert_eq!(pool.allocated(), layers);
+}